@@ -1,15 +1,28 @@
 use crate::primitives::*;
 use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CreateAccountInput {
+    #[serde(default)]
+    pub mnemonic: bool,
+    pub derivation_path: Option<String>,
+    pub password: Option<String>,
+    pub scheme: Option<CryptoScheme>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateAccountOutput {
     pub seed: Seed,
+    pub derivation_path: Option<String>,
+    pub public_key: String,
     pub account: Account,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FundAccountInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub to: Account,
     pub amount: Balance,
 }
@@ -24,38 +37,51 @@ pub struct FundAccountOutput {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccountBalanceInput {
     pub account: Account,
+    pub at: Option<BlockAt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccountBalanceOutput {
     pub balance: Balance,
+    #[serde(flatten)]
+    pub block: AtBlock,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccountExistsInput {
     pub account: Account,
+    pub at: Option<BlockAt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AccountExistsOutput {
     pub account: Account,
     pub exists: bool,
+    #[serde(flatten)]
+    pub block: AtBlock,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SeededAccountInput {
     pub seed: Seed,
+    pub derivation_path: Option<String>,
+    pub password: Option<String>,
+    pub scheme: Option<CryptoScheme>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SeededAccountOutput {
     pub seed: Seed,
+    pub derivation_path: Option<String>,
+    pub public_key: String,
     pub account: Account,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SetBalanceInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub to: Account,
     pub amount: Balance,
 }
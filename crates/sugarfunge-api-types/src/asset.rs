@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateClassInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub class_id: ClassId,
     pub metadata: serde_json::Value,
     pub owner: Account,
@@ -18,6 +20,7 @@ pub struct CreateClassOutput {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ClassInfoInput {
     pub class_id: ClassId,
+    pub at: Option<BlockAt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -25,16 +28,41 @@ pub struct ClassInfo {
     pub class_id: ClassId,
     pub owner: Account,
     pub metadata: serde_json::Value,
+    /// Block from which the class has had its current metadata, `None` when
+    /// the node pruned the state it is looked up in
+    pub metadata_changed_at: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ClassInfoOutput {
     pub info: Option<ClassInfo>,
+    #[serde(flatten)]
+    pub block: AtBlock,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateClassMetadataInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub class_id: ClassId,
+    pub metadata: serde_json::Value,
+}
+
+/// The runtime emits no event on class metadata updates, the output
+/// carries the metadata read back at the block including the extrinsic
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClassMetadataUpdatedOutput {
+    pub class_id: ClassId,
+    pub metadata: serde_json::Value,
+    pub block_hash: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub class_id: ClassId,
     pub asset_id: AssetId,
     pub metadata: serde_json::Value,
@@ -51,6 +79,7 @@ pub struct CreateOutput {
 pub struct AssetInfoInput {
     pub class_id: ClassId,
     pub asset_id: AssetId,
+    pub at: Option<BlockAt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -63,11 +92,125 @@ pub struct AssetInfo {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AssetInfoOutput {
     pub info: Option<AssetInfo>,
+    #[serde(flatten)]
+    pub block: AtBlock,
+}
+
+/// Page of a listing, `cursor` is the `next_cursor` of the previous page
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ClassesInput {
+    /// Only the classes owned by that account
+    pub owner: Option<Account>,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub at: Option<BlockAt>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClassItem {
+    pub class_id: ClassId,
+    pub owner: Account,
+    pub metadata: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClassesOutput {
+    pub classes: Vec<ClassItem>,
+    /// `None` once the listing is complete
+    pub next_cursor: Option<String>,
+    #[serde(flatten)]
+    pub block: AtBlock,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AssetsInput {
+    /// Only the assets of that class
+    pub class_id: Option<ClassId>,
+    /// Only the assets of classes owned by that account
+    pub owner: Option<Account>,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub at: Option<BlockAt>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssetsOutput {
+    pub assets: Vec<AssetInfo>,
+    /// `None` once the listing is complete
+    pub next_cursor: Option<String>,
+    #[serde(flatten)]
+    pub block: AtBlock,
+}
+
+/// Order of the holders of an asset, ties broken by account
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderOrder {
+    #[default]
+    AmountDesc,
+    AmountAsc,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HoldersInput {
+    pub class_id: ClassId,
+    pub asset_id: AssetId,
+    pub order: Option<HolderOrder>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HolderOutput {
+    pub account: Account,
+    pub amount: Balance,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HoldersOutput {
+    pub holders: Vec<HolderOutput>,
+    /// Number of accounts holding the asset
+    pub total: usize,
+    /// `None` once the listing is complete
+    pub next_cursor: Option<String>,
+    #[serde(flatten)]
+    pub index: IndexStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupplyInput {
+    pub class_id: ClassId,
+    pub asset_id: AssetId,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SupplyOutput {
+    pub class_id: ClassId,
+    pub asset_id: AssetId,
+    pub supply: Balance,
+    pub holders: usize,
+    #[serde(flatten)]
+    pub index: IndexStatus,
+}
+
+/// Freshness of the holder index serving a response
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IndexStatus {
+    /// Finalized block the index is up to date with
+    #[serde(flatten)]
+    pub block: AtBlock,
+    /// Latest finalized block seen by the index
+    pub finalized_block_number: Option<u32>,
+    /// Set while the index lags behind the finalized blocks or can't follow them
+    pub stale: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UpdateMetadataInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub class_id: ClassId,
     pub asset_id: AssetId,
     pub metadata: serde_json::Value,
@@ -83,7 +226,9 @@ pub struct UpdateMetadataOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MintInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub to: Account,
     pub class_id: ClassId,
     pub asset_id: AssetId,
@@ -101,7 +246,9 @@ pub struct MintOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BurnInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub from: Account,
     pub class_id: ClassId,
     pub asset_id: AssetId,
@@ -117,16 +264,69 @@ pub struct BurnOutput {
     pub who: Account,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchMintInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub to: Account,
+    pub class_id: ClassId,
+    pub asset_ids: Vec<AssetId>,
+    pub amounts: Vec<Balance>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchMintItemOutput {
+    pub to: Account,
+    pub class_id: ClassId,
+    pub asset_ids: Vec<AssetId>,
+    pub amounts: Vec<Balance>,
+    pub who: Account,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchMintOutput {
+    pub events: Vec<BatchMintItemOutput>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchBurnInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub from: Account,
+    pub class_id: ClassId,
+    pub asset_ids: Vec<AssetId>,
+    pub amounts: Vec<Balance>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchBurnItemOutput {
+    pub from: Account,
+    pub class_id: ClassId,
+    pub asset_ids: Vec<AssetId>,
+    pub amounts: Vec<Balance>,
+    pub who: Account,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchBurnOutput {
+    pub events: Vec<BatchBurnItemOutput>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AssetBalanceInput {
     pub account: Account,
     pub class_id: ClassId,
     pub asset_id: AssetId,
+    pub at: Option<BlockAt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AssetBalanceOutput {
     pub amount: Balance,
+    #[serde(flatten)]
+    pub block: AtBlock,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -134,11 +334,14 @@ pub struct AssetBalancesInput {
     pub account: Account,
     pub class_id: Option<ClassId>,
     pub asset_id: Option<AssetId>,
+    pub at: Option<BlockAt>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AssetBalancesOutput {
     pub balances: Vec<AssetBalanceItemOutput>,
+    #[serde(flatten)]
+    pub block: AtBlock,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -150,7 +353,9 @@ pub struct AssetBalanceItemOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TransferFromInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub from: Account,
     pub to: Account,
     pub class_id: ClassId,
@@ -167,3 +372,30 @@ pub struct TransferFromOutput {
     pub amount: Balance,
     pub who: Account,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchTransferFromInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub from: Account,
+    pub to: Account,
+    pub class_id: ClassId,
+    pub asset_ids: Vec<AssetId>,
+    pub amounts: Vec<Balance>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchTransferFromItemOutput {
+    pub from: Account,
+    pub to: Account,
+    pub class_id: ClassId,
+    pub asset_ids: Vec<AssetId>,
+    pub amounts: Vec<Balance>,
+    pub who: Account,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchTransferFromOutput {
+    pub events: Vec<BatchTransferFromItemOutput>,
+}
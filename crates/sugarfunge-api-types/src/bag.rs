@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RegisterInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub class_id: ClassId,
     pub metadata: serde_json::Value,
 }
@@ -16,7 +18,9 @@ pub struct RegisterOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub class_id: ClassId,
     pub owners: Vec<Account>,
     pub shares: Vec<Balance>,
@@ -32,7 +36,9 @@ pub struct CreateOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SweepInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub bag: Account,
     pub to: Account,
 }
@@ -46,7 +52,9 @@ pub struct SweepOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DepositInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub bag: Account,
     pub class_ids: Vec<ClassId>,
     pub asset_ids: Vec<Vec<AssetId>>,
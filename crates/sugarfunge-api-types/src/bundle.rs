@@ -10,7 +10,9 @@ pub struct BundleSchema {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RegisterBundleInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub class_id: ClassId,
     pub asset_id: AssetId,
     pub schema: BundleSchema,
@@ -27,7 +29,9 @@ pub struct RegisterBundleOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MintBundleInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub from: Account,
     pub to: Account,
     pub bundle_id: BundleId,
@@ -45,7 +49,9 @@ pub struct MintBundleOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BurnBundleInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub from: Account,
     pub to: Account,
     pub bundle_id: BundleId,
@@ -61,9 +67,16 @@ pub struct BurnBundleOutput {
     pub amount: Balance,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetBundlesInput {
+    pub at: Option<BlockAt>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetBundles {
     pub bundles: Vec<BundleItem>,
+    #[serde(flatten)]
+    pub block: AtBlock,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -76,6 +89,8 @@ pub struct BundleItem {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetBundlesData {
     pub bundles: Vec<BundleDataItem>,
+    #[serde(flatten)]
+    pub block: AtBlock,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
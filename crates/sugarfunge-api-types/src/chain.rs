@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ChainConstantsInput {
+    pub pallet: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChainConstantsOutput {
+    /// Values of the constants by pallet and name
+    pub constants: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChainInfoOutput {
+    pub chain: String,
+    pub token_symbol: Option<String>,
+    pub token_decimals: Option<u32>,
+    pub ss58_prefix: u16,
+    pub genesis_hash: String,
+    pub spec_name: Option<String>,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
@@ -0,0 +1,143 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProposeInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    /// Module and operation of the proposed call, e.g. `asset` and `mint`
+    pub module: String,
+    pub operation: String,
+    /// Input of the operation, its signer is not used
+    pub input: serde_json::Value,
+    /// Number of ayes needed to approve it, below 2 it is executed at once
+    pub threshold: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProposeOutput {
+    pub proposal_hash: String,
+    /// Index to vote with, `None` when executed at once
+    pub proposal_index: Option<u32>,
+    pub threshold: u32,
+    pub executed: bool,
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VoteInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub proposal_hash: String,
+    pub proposal_index: u32,
+    pub approve: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VoteOutput {
+    pub account: Account,
+    pub proposal_hash: String,
+    pub approve: bool,
+    pub ayes: u32,
+    pub nays: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub proposal_hash: String,
+    pub proposal_index: u32,
+    /// Length of the proposal, looked up by `council/close` if not given
+    pub length_bound: Option<u32>,
+    /// Weight of the proposal, looked up by `council/close` if not given
+    pub weight_bound: Option<Weight>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseOutput {
+    pub proposal_hash: String,
+    pub approved: bool,
+    pub ayes: u32,
+    pub nays: u32,
+    pub executed: bool,
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DisapproveInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub proposal_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DisapproveOutput {
+    pub proposal_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecuteInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub module: String,
+    pub operation: String,
+    pub input: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecuteOutput {
+    pub proposal_hash: String,
+    pub success: bool,
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetMembersInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub members: Vec<Account>,
+    pub prime: Option<Account>,
+    /// Current number of members, bounding the weight of removing their votes,
+    /// read from the latest block by `council/set_members` if not given
+    pub old_count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetMembersOutput {
+    pub members: Vec<Account>,
+    pub prime: Option<Account>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ProposalsInput {
+    pub at: Option<BlockAt>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProposalOutput {
+    pub proposal_hash: String,
+    pub proposal_index: u32,
+    pub threshold: u32,
+    pub ayes: Vec<Account>,
+    pub nays: Vec<Account>,
+    /// Block from which the proposal can be closed without all votes
+    pub end: u32,
+    /// Pallet and call names, e.g. `Asset.mint`
+    pub call: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProposalsOutput {
+    pub members: Vec<Account>,
+    pub prime: Option<Account>,
+    pub proposals: Vec<ProposalOutput>,
+    #[serde(flatten)]
+    pub block: AtBlock,
+}
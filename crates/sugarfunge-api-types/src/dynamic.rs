@@ -0,0 +1,25 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DynamicTxInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub pallet: String,
+    pub call: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventOutput {
+    pub pallet: String,
+    pub variant: String,
+    pub fields: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DynamicTxOutput {
+    pub events: Vec<EventOutput>,
+}
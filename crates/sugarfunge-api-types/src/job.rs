@@ -0,0 +1,88 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AirdropMode {
+    Mint,
+    Transfer,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AirdropRow {
+    pub account: Account,
+    pub class_id: ClassId,
+    pub asset_id: AssetId,
+    pub amount: Balance,
+}
+
+/// Rows are given either as JSON `rows` or as `csv` lines of
+/// `account,class_id,asset_id,amount`, `from` is required to transfer
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AirdropInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub mode: AirdropMode,
+    pub from: Option<Account>,
+    pub rows: Option<Vec<AirdropRow>>,
+    pub csv: Option<String>,
+    pub chunk_size: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// `unknown` rows were submitted but the API stopped before their outcome
+/// was known, they are only retried on request
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRowStatus {
+    Pending,
+    Submitted,
+    Succeeded,
+    Failed,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobRowOutput {
+    pub row: usize,
+    #[serde(flatten)]
+    pub item: AirdropRow,
+    pub status: JobRowStatus,
+    pub extrinsic_hash: Option<String>,
+    pub block_hash: Option<String>,
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JobOutput {
+    pub job_id: String,
+    pub state: JobState,
+    pub total: usize,
+    pub pending: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub unknown: usize,
+    /// Why the job could not be saved, its progress is lost on restart
+    pub save_error: Option<String>,
+    pub rows: Vec<JobRowOutput>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JobInput {
+    pub job_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RetryJobInput {
+    pub job_id: String,
+    #[serde(default)]
+    pub include_unknown: bool,
+}
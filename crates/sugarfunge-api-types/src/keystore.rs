@@ -0,0 +1,32 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GenerateKeyInput {
+    pub key_id: Option<KeyId>,
+    pub scheme: Option<CryptoScheme>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImportKeyInput {
+    pub key_id: Option<KeyId>,
+    pub seed: Seed,
+    pub scheme: Option<CryptoScheme>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KeyOutput {
+    pub key_id: KeyId,
+    pub account: Account,
+    pub scheme: CryptoScheme,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListKeysOutput {
+    pub keys: Vec<KeyOutput>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoveKeyInput {
+    pub key_id: KeyId,
+}
@@ -13,6 +13,16 @@ pub mod account;
 pub mod asset;
 pub mod bag;
 pub mod bundle;
+pub mod chain;
+pub mod council;
+pub mod dynamic;
+pub mod job;
+pub mod keystore;
 pub mod market;
 pub mod primitives;
+pub mod scheduler;
+pub mod storage;
+pub mod sudo;
+pub mod tx;
+pub mod validation;
 pub mod validator;
@@ -208,7 +208,9 @@ pub struct Rates {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateMarketInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub market_id: MarketId,
 }
 
@@ -220,7 +222,9 @@ pub struct CreateMarketOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateMarketRateInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub market_id: MarketId,
     pub market_rate_id: MarketId,
     pub rates: Rates,
@@ -234,7 +238,9 @@ pub struct CreateMarketRateOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DepositAssetsInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub market_id: MarketId,
     pub market_rate_id: MarketId,
     pub amount: Balance,
@@ -252,7 +258,9 @@ pub struct DepositAssetsOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ExchangeAssetsInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub market_id: MarketId,
     pub market_rate_id: MarketId,
     pub amount: Balance,
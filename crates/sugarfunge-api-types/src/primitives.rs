@@ -1,8 +1,7 @@
-use std::str::FromStr;
-
 use serde::{Deserialize, Serialize};
 
 use sp_core;
+use sp_core::crypto::Ss58Codec;
 
 use bevy_derive::{Deref, DerefMut};
 
@@ -27,6 +26,98 @@ impl Seed {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Deref, DerefMut)]
+pub struct KeyId(String);
+
+impl From<String> for KeyId {
+    fn from(key_id: String) -> KeyId {
+        KeyId(key_id)
+    }
+}
+
+impl From<&KeyId> for String {
+    fn from(key_id: &KeyId) -> String {
+        key_id.0.clone()
+    }
+}
+
+impl KeyId {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CryptoScheme {
+    #[default]
+    Sr25519,
+    Ed25519,
+    Ecdsa,
+}
+
+/// Key used to sign an extrinsic, either a raw seed or a key held by the
+/// server keystore and referenced by its key id or account.
+/// Exactly one of `seed`, `key_id` and `signer_account` must be set,
+/// the derivation path (e.g. `//hard/soft`) and password apply to either.
+/// The scheme only applies to raw seeds, keystore keys keep their own.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Signer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<Seed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<KeyId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_account: Option<Account>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<CryptoScheme>,
+}
+
+/// How long a request waits on its extrinsic before responding
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitMode {
+    Submitted,
+    InBlock,
+    #[default]
+    Finalized,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Weight {
+    pub ref_time: u64,
+    pub proof_size: u64,
+}
+
+/// Optional settings of a submitted extrinsic, `mortality` is the number
+/// of blocks from the latest one during which it remains valid
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TxOptions {
+    pub tip: Option<Balance>,
+    pub mortality: Option<u64>,
+    pub nonce: Option<u64>,
+    pub wait: Option<WaitMode>,
+}
+
+/// Block a read is evaluated at, by hash or by number
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum BlockAt {
+    Number(u32),
+    Hash(String),
+}
+
+/// Block a read was evaluated at
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AtBlock {
+    pub block_hash: String,
+    pub block_number: u32,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Deref, DerefMut)]
 pub struct Account(String);
 
@@ -48,17 +139,33 @@ impl From<subxt::utils::AccountId32> for Account {
     }
 }
 
+/// Parse an SS58 address of any crypto scheme, or a hex encoded public key.
+/// 33 byte ecdsa public keys map to the account hashed from them.
+pub fn parse_account_id(
+    account: &str,
+) -> Result<sp_core::crypto::AccountId32, sp_core::crypto::PublicError> {
+    let Some(public) = account.strip_prefix("0x") else {
+        return sp_core::crypto::AccountId32::from_ss58check(account);
+    };
+    let public = hex::decode(public).map_err(|_| sp_core::crypto::PublicError::InvalidFormat)?;
+    match public.len() {
+        32 => Ok(sp_core::crypto::AccountId32::new(
+            <[u8; 32]>::try_from(public).unwrap(),
+        )),
+        33 => Ok(sp_core::crypto::AccountId32::new(
+            sp_core::hashing::blake2_256(&public),
+        )),
+        _ => Err(sp_core::crypto::PublicError::BadLength),
+    }
+}
+
 impl TryFrom<&Account> for sp_core::crypto::AccountId32 {
     type Error = sp_core::crypto::PublicError;
 
     fn try_from(
         account: &Account,
     ) -> Result<sp_core::crypto::AccountId32, sp_core::crypto::PublicError> {
-        let account = sp_core::sr25519::Public::from_str(account.as_str());
-        match account {
-            Ok(account) => Ok(sp_core::crypto::AccountId32::from(account)),
-            Err(err) => Err(err),
-        }
+        parse_account_id(account.as_str())
     }
 }
 
@@ -68,13 +175,8 @@ impl TryFrom<&Account> for subxt::utils::AccountId32 {
     fn try_from(
         account: &Account,
     ) -> Result<subxt::utils::AccountId32, sp_core::crypto::PublicError> {
-        let account = sp_core::sr25519::Public::from_str(account.as_str());
-        match account {
-            Ok(account) => Ok(subxt::utils::AccountId32::from(
-                account.as_array_ref().to_owned(),
-            )),
-            Err(err) => Err(err),
-        }
+        let account = parse_account_id(account.as_str())?;
+        Ok(subxt::utils::AccountId32::from(<[u8; 32]>::from(account)))
     }
 }
 
@@ -215,6 +317,17 @@ impl ValidatorId {
     }
 }
 
+impl TryFrom<&ValidatorId> for subxt::utils::AccountId32 {
+    type Error = sp_core::crypto::PublicError;
+
+    fn try_from(
+        validator_id: &ValidatorId,
+    ) -> Result<subxt::utils::AccountId32, sp_core::crypto::PublicError> {
+        let account = parse_account_id(validator_id.as_str())?;
+        Ok(subxt::utils::AccountId32::from(<[u8; 32]>::from(account)))
+    }
+}
+
 pub fn transform_vec_account_to_string(in_vec: Vec<Account>) -> Vec<String> {
     in_vec
         .into_iter()
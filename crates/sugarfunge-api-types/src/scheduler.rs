@@ -0,0 +1,106 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+/// Origin the schedule is made with, and its calls dispatched with
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleOrigin {
+    /// Scheduled through sudo, calls are dispatched as root
+    #[default]
+    Root,
+    /// Scheduled by the signer, calls are dispatched as the signer
+    Signed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct SchedulePeriod {
+    /// Blocks between two runs
+    pub period: u32,
+    /// Number of runs, the first one included
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScheduleInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    /// Module and operation of the scheduled call, e.g. `asset` and `mint`
+    pub module: String,
+    pub operation: String,
+    /// Input of the operation, its signer is not used
+    pub input: serde_json::Value,
+    /// Block number to run at
+    pub at: Option<u32>,
+    /// Number of blocks to run after
+    pub after: Option<u32>,
+    pub periodic: Option<SchedulePeriod>,
+    pub priority: Option<u8>,
+    /// Name to cancel the schedule by, at most 32 bytes
+    pub name: Option<String>,
+    pub origin: Option<ScheduleOrigin>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScheduleOutput {
+    pub when: u32,
+    pub index: u32,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelNamedInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub name: String,
+    pub origin: Option<ScheduleOrigin>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CancelNamedOutput {
+    pub when: u32,
+    pub index: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SchedulesInput {
+    /// Only the schedule of that name
+    pub name: Option<String>,
+    pub at: Option<BlockAt>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScheduleItemOutput {
+    pub when: u32,
+    pub index: u32,
+    /// Name of the schedule, or its hex encoded id if not valid text
+    pub name: Option<String>,
+    pub priority: u8,
+    pub periodic: Option<SchedulePeriod>,
+    /// Pallet and call names, e.g. `Asset.mint`, when stored inline
+    pub call: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SchedulesOutput {
+    pub schedules: Vec<ScheduleItemOutput>,
+    #[serde(flatten)]
+    pub block: AtBlock,
+}
+
+/// Id of a named schedule, the name padded with zeroes
+pub fn schedule_id(name: &str) -> Option<[u8; 32]> {
+    let mut id = [0u8; 32];
+    id.get_mut(..name.len())?.copy_from_slice(name.as_bytes());
+    Some(id)
+}
+
+/// Name of a schedule id, if it was made by [`schedule_id`]
+pub fn schedule_name(id: &[u8; 32]) -> String {
+    let name = id.split(|byte| *byte == 0).next().unwrap_or_default();
+    match std::str::from_utf8(name) {
+        Ok(name) if id[name.len()..].iter().all(|byte| *byte == 0) => name.to_string(),
+        _ => format!("0x{}", hex::encode(id)),
+    }
+}
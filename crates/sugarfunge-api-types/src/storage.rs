@@ -0,0 +1,40 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StorageInput {
+    /// Keys of a map entry, fewer than the entry has iterate over the rest
+    #[serde(default)]
+    pub keys: Vec<serde_json::Value>,
+    /// Block hash or number, `block_hash` is accepted as well
+    #[serde(alias = "block_hash")]
+    pub at: Option<BlockAt>,
+    /// Maximum number of entries returned when iterating, 100 by default
+    /// and at most 1000
+    pub limit: Option<u32>,
+    /// `next_key` of the previous page
+    pub start_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StorageEntryOutput {
+    /// Hex encoded storage key
+    pub key: String,
+    /// Keys of the map decoded from the storage key, null for keys hashed
+    /// without their value
+    pub keys: Vec<serde_json::Value>,
+    pub value: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StorageOutput {
+    #[serde(flatten)]
+    pub block: AtBlock,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<Vec<StorageEntryOutput>>,
+    /// Set when more entries follow, to be passed as `start_key`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_key: Option<String>,
+}
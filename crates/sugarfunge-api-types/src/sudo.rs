@@ -0,0 +1,33 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMode {
+    #[default]
+    Sudo,
+    SudoAs,
+    SudoUncheckedWeight,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SudoCallInput {
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
+    pub pallet: String,
+    pub call: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+    pub mode: Option<SudoMode>,
+    /// Origin of the call with `sudo_as`
+    pub who: Option<Account>,
+    /// Weight of the call with `sudo_unchecked_weight`
+    pub weight: Option<Weight>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SudoCallOutput {
+    pub success: bool,
+    pub error: Option<serde_json::Value>,
+}
@@ -0,0 +1,111 @@
+use crate::primitives::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum Era {
+    Immortal,
+    Mortal {
+        period: u64,
+        block_number: u64,
+        block_hash: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PrepareExtrinsicOutput {
+    pub call_data: String,
+    pub signer_payload: String,
+    pub signer_account: Account,
+    pub nonce: u64,
+    pub era: Era,
+    pub genesis_hash: String,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubmitExtrinsicInput {
+    pub input: serde_json::Value,
+    pub nonce: u64,
+    pub era: Era,
+    pub signature: String,
+    pub scheme: Option<CryptoScheme>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubmittedExtrinsicOutput {
+    pub tracking_id: String,
+    pub extrinsic_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ExtrinsicStatus {
+    Ready,
+    InBlock,
+    Finalized,
+    Dropped,
+    Invalid,
+    /// The node stopped reporting the status before a final one
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxStatusInput {
+    pub tracking_id: String,
+}
+
+/// Status of a tracked extrinsic, `output` holds the decoded result of
+/// the operation once in a block and `error` why it failed if it did
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TxStatusOutput {
+    pub tracking_id: String,
+    pub extrinsic_hash: String,
+    pub status: ExtrinsicStatus,
+    pub block_hash: Option<String>,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WeightOutput {
+    pub ref_time: u64,
+    pub proof_size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InclusionFeeOutput {
+    pub base_fee: Balance,
+    pub len_fee: Balance,
+    pub adjusted_weight_fee: Balance,
+}
+
+/// Fee of an operation as computed by the transaction payment pallet,
+/// `partial_fee` is the inclusion fee, without the tip
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FeeEstimateOutput {
+    pub partial_fee: Balance,
+    pub weight: WeightOutput,
+    pub class: String,
+    pub inclusion_fee: Option<InclusionFeeOutput>,
+    pub tip: Balance,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DryRunOutcome {
+    Success,
+    DispatchError,
+    Invalid,
+}
+
+/// Outcome of an operation applied to the best block without submitting
+/// it, `error` holds the decoded dispatch error if it failed and `fee`
+/// what it would pay unless invalid
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DryRunOutput {
+    pub outcome: DryRunOutcome,
+    pub error: Option<serde_json::Value>,
+    pub fee: Option<FeeEstimateOutput>,
+    /// Events the extrinsic would emit, always `None` as the runtime has no
+    /// `DryRunApi` and `system_dryRun` only reports the dispatch result
+    pub events: Option<Vec<serde_json::Value>>,
+}
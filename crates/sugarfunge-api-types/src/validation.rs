@@ -0,0 +1,643 @@
+use crate::account::*;
+use crate::asset::*;
+use crate::bag;
+use crate::bundle::*;
+use crate::council;
+use crate::dynamic::*;
+use crate::market::*;
+use crate::primitives::*;
+use crate::scheduler::*;
+use crate::sudo::*;
+use crate::validator::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+/// Limits set by the runtime constants, `None` when the runtime has none
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    pub max_class_metadata: Option<u32>,
+    pub max_asset_metadata: Option<u32>,
+    pub max_bundle_assets: Option<u32>,
+    pub max_bag_owners: Option<u32>,
+    pub max_market_rates: Option<u32>,
+}
+
+/// Collects the fields of a request that can't be submitted as they are
+pub struct Validator {
+    pub limits: Limits,
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn error(&mut self, field: &str, reason: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    pub fn account(&mut self, field: &str, account: &str) {
+        if parse_account_id(account).is_err() {
+            self.error(field, "invalid account");
+        }
+    }
+
+    pub fn accounts(&mut self, field: &str, accounts: &[Account]) {
+        for (index, account) in accounts.iter().enumerate() {
+            self.account(&format!("{}[{}]", field, index), account);
+        }
+    }
+
+    /// Hex encoded 32 byte hash
+    pub fn hash(&mut self, field: &str, hash: &str) {
+        let hash = hash.strip_prefix("0x").unwrap_or(hash);
+        if !matches!(hex::decode(hash), Ok(bytes) if bytes.len() == 32) {
+            self.error(field, "expected a 32 byte hex hash");
+        }
+    }
+
+    pub fn same_len(&mut self, field: &str, len: usize, other: &str, other_len: usize) {
+        if len != other_len {
+            self.error(
+                field,
+                format!("has length {} but {} has length {}", len, other, other_len),
+            );
+        }
+    }
+
+    pub fn max_len(&mut self, field: &str, len: usize, max: Option<u32>) {
+        match max {
+            Some(max) if len > max as usize => {
+                self.error(field, format!("has length {}, at most {}", len, max))
+            }
+            _ => {}
+        }
+    }
+
+    pub fn not_empty(&mut self, field: &str, len: usize) {
+        if len == 0 {
+            self.error(field, "must not be empty");
+        }
+    }
+
+    /// Metadata stored as its JSON encoding
+    pub fn metadata(&mut self, field: &str, metadata: &serde_json::Value, max: Option<u32>) {
+        match (serde_json::to_vec(metadata), max) {
+            (Err(e), _) => self.error(field, e.to_string()),
+            (Ok(bytes), Some(max)) if bytes.len() > max as usize => self.error(
+                field,
+                format!("is {} bytes once encoded, at most {}", bytes.len(), max),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Asset ids and amounts of several classes, each class with its own list
+    pub fn class_assets(
+        &mut self,
+        field: &str,
+        class_ids: &[ClassId],
+        asset_ids: &[Vec<AssetId>],
+        amounts: &[Vec<Balance>],
+        max: Option<u32>,
+    ) {
+        let prefix = |name: &str| match field {
+            "" => name.to_string(),
+            field => format!("{}.{}", field, name),
+        };
+        self.not_empty(&prefix("class_ids"), class_ids.len());
+        self.max_len(&prefix("class_ids"), class_ids.len(), max);
+        self.same_len(
+            &prefix("asset_ids"),
+            asset_ids.len(),
+            &prefix("class_ids"),
+            class_ids.len(),
+        );
+        self.same_len(
+            &prefix("amounts"),
+            amounts.len(),
+            &prefix("class_ids"),
+            class_ids.len(),
+        );
+        for (index, (asset_ids, amounts)) in asset_ids.iter().zip(amounts).enumerate() {
+            let asset_ids_field = format!("{}[{}]", prefix("asset_ids"), index);
+            let amounts_field = format!("{}[{}]", prefix("amounts"), index);
+            self.max_len(&asset_ids_field, asset_ids.len(), max);
+            self.same_len(
+                &amounts_field,
+                amounts.len(),
+                &asset_ids_field,
+                asset_ids.len(),
+            );
+        }
+    }
+
+    pub fn schedule_name(&mut self, field: &str, name: &str) {
+        if name.is_empty() || schedule_id(name).is_none() {
+            self.error(field, "must be between 1 and 32 bytes");
+        }
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(self.errors),
+        }
+    }
+}
+
+/// Checks run on the input of an operation before it is signed
+pub trait Validate {
+    fn validate(&self, validator: &mut Validator);
+}
+
+impl Validate for FundAccountInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("to", &self.to);
+    }
+}
+
+impl Validate for SetBalanceInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("to", &self.to);
+    }
+}
+
+impl Validate for CreateClassInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("owner", &self.owner);
+        let max = validator.limits.max_class_metadata;
+        validator.metadata("metadata", &self.metadata, max);
+    }
+}
+
+impl Validate for UpdateClassMetadataInput {
+    fn validate(&self, validator: &mut Validator) {
+        let max = validator.limits.max_class_metadata;
+        validator.metadata("metadata", &self.metadata, max);
+    }
+}
+
+impl Validate for CreateInput {
+    fn validate(&self, validator: &mut Validator) {
+        let max = validator.limits.max_asset_metadata;
+        validator.metadata("metadata", &self.metadata, max);
+    }
+}
+
+impl Validate for UpdateMetadataInput {
+    fn validate(&self, validator: &mut Validator) {
+        let max = validator.limits.max_asset_metadata;
+        validator.metadata("metadata", &self.metadata, max);
+    }
+}
+
+impl Validate for MintInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("to", &self.to);
+    }
+}
+
+impl Validate for BurnInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("from", &self.from);
+    }
+}
+
+impl Validate for TransferFromInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("from", &self.from);
+        validator.account("to", &self.to);
+    }
+}
+
+impl Validate for BatchMintInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("to", &self.to);
+        validator.not_empty("asset_ids", self.asset_ids.len());
+        validator.same_len(
+            "amounts",
+            self.amounts.len(),
+            "asset_ids",
+            self.asset_ids.len(),
+        );
+    }
+}
+
+impl Validate for BatchBurnInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("from", &self.from);
+        validator.not_empty("asset_ids", self.asset_ids.len());
+        validator.same_len(
+            "amounts",
+            self.amounts.len(),
+            "asset_ids",
+            self.asset_ids.len(),
+        );
+    }
+}
+
+impl Validate for BatchTransferFromInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("from", &self.from);
+        validator.account("to", &self.to);
+        validator.not_empty("asset_ids", self.asset_ids.len());
+        validator.same_len(
+            "amounts",
+            self.amounts.len(),
+            "asset_ids",
+            self.asset_ids.len(),
+        );
+    }
+}
+
+impl Validate for bag::RegisterInput {
+    fn validate(&self, validator: &mut Validator) {
+        let max = validator.limits.max_class_metadata;
+        validator.metadata("metadata", &self.metadata, max);
+    }
+}
+
+impl Validate for bag::CreateInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.accounts("owners", &self.owners);
+        validator.not_empty("owners", self.owners.len());
+        let max = validator.limits.max_bag_owners;
+        validator.max_len("owners", self.owners.len(), max);
+        validator.same_len("shares", self.shares.len(), "owners", self.owners.len());
+    }
+}
+
+impl Validate for bag::SweepInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("bag", &self.bag);
+        validator.account("to", &self.to);
+    }
+}
+
+impl Validate for bag::DepositInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("bag", &self.bag);
+        validator.class_assets("", &self.class_ids, &self.asset_ids, &self.amounts, None);
+    }
+}
+
+impl Validate for RegisterBundleInput {
+    fn validate(&self, validator: &mut Validator) {
+        let max = validator.limits.max_bundle_assets;
+        validator.class_assets(
+            "schema",
+            &self.schema.class_ids,
+            &self.schema.asset_ids,
+            &self.schema.amounts,
+            max,
+        );
+        let max = validator.limits.max_asset_metadata;
+        validator.metadata("metadata", &self.metadata, max);
+    }
+}
+
+impl Validate for MintBundleInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("from", &self.from);
+        validator.account("to", &self.to);
+        validator.hash("bundle_id", &self.bundle_id);
+    }
+}
+
+impl Validate for BurnBundleInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("from", &self.from);
+        validator.account("to", &self.to);
+        validator.hash("bundle_id", &self.bundle_id);
+    }
+}
+
+impl Validate for AddValidatorInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("validator_id", &self.validator_id);
+    }
+}
+
+impl Validate for RemoveValidatorInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("validator_id", &self.validator_id);
+    }
+}
+
+impl Validate for SetKeysInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.account("aura", &self.aura);
+        validator.account("grandpa", &self.grandpa);
+    }
+}
+
+impl Validate for CreateMarketInput {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+impl Validate for CreateMarketRateInput {
+    fn validate(&self, validator: &mut Validator) {
+        let rates = &self.rates.rates;
+        validator.not_empty("rates.rates", rates.len());
+        let max = validator.limits.max_market_rates;
+        validator.max_len("rates.rates", rates.len(), max);
+        for (index, rate) in rates.iter().enumerate() {
+            for (name, account) in [("from", &rate.from), ("to", &rate.to)] {
+                if let RateAccount::Account(account) = account {
+                    validator.account(&format!("rates.rates[{}].{}", index, name), account);
+                }
+            }
+        }
+    }
+}
+
+impl Validate for DepositAssetsInput {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+impl Validate for ExchangeAssetsInput {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+impl Validate for ScheduleInput {
+    fn validate(&self, validator: &mut Validator) {
+        match (self.at, self.after) {
+            (Some(_), Some(_)) => validator.error("after", "only one of at or after may be given"),
+            (None, None) => validator.error("at", "one of at or after is required"),
+            _ => {}
+        }
+        if let Some(periodic) = self.periodic {
+            if periodic.period == 0 {
+                validator.error("periodic.period", "must be at least 1");
+            }
+            if periodic.count < 2 {
+                validator.error("periodic.count", "must be at least 2");
+            }
+        }
+        if let Some(name) = &self.name {
+            validator.schedule_name("name", name);
+        }
+    }
+}
+
+impl Validate for CancelNamedInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.schedule_name("name", &self.name);
+    }
+}
+
+impl Validate for council::ProposeInput {
+    fn validate(&self, validator: &mut Validator) {
+        if self.threshold == 0 {
+            validator.error("threshold", "must be at least 1");
+        }
+    }
+}
+
+impl Validate for council::VoteInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.hash("proposal_hash", &self.proposal_hash);
+    }
+}
+
+impl Validate for council::CloseInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.hash("proposal_hash", &self.proposal_hash);
+    }
+}
+
+impl Validate for council::DisapproveInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.hash("proposal_hash", &self.proposal_hash);
+    }
+}
+
+impl Validate for council::ExecuteInput {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+impl Validate for council::SetMembersInput {
+    fn validate(&self, validator: &mut Validator) {
+        validator.accounts("members", &self.members);
+        if let Some(prime) = &self.prime {
+            validator.account("prime", prime);
+            if !self
+                .members
+                .iter()
+                .any(|member| member.as_str() == prime.as_str())
+            {
+                validator.error("prime", "must be one of the members");
+            }
+        }
+    }
+}
+
+impl Validate for SudoCallInput {
+    fn validate(&self, validator: &mut Validator) {
+        match (self.mode.unwrap_or_default(), &self.who, &self.weight) {
+            (SudoMode::SudoAs, Some(who), _) => validator.account("who", who),
+            (SudoMode::SudoAs, None, _) => validator.error("who", "required with sudo_as"),
+            (SudoMode::SudoUncheckedWeight, _, None) => {
+                validator.error("weight", "required with sudo_unchecked_weight")
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Arguments are checked against the metadata as they are encoded
+impl Validate for DynamicTxInput {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn errors(input: &impl Validate, limits: Limits) -> Vec<FieldError> {
+        let mut validator = Validator::new(limits);
+        input.validate(&mut validator);
+        validator.finish().err().unwrap_or_default()
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    fn batch_mint(to: &str, asset_ids: Vec<u64>, amounts: Vec<u128>) -> BatchMintInput {
+        BatchMintInput {
+            signer: Signer::default(),
+            tx_options: None,
+            to: Account::from(to.to_string()),
+            class_id: ClassId::from(1),
+            asset_ids: asset_ids.into_iter().map(AssetId::from).collect(),
+            amounts: amounts.into_iter().map(Balance::from).collect(),
+        }
+    }
+
+    fn create_class(metadata: serde_json::Value) -> CreateClassInput {
+        CreateClassInput {
+            signer: Signer::default(),
+            tx_options: None,
+            class_id: ClassId::from(1),
+            metadata,
+            owner: Account::from(ALICE.to_string()),
+        }
+    }
+
+    #[test]
+    fn valid_input_passes() {
+        let input = batch_mint(ALICE, vec![1, 2], vec![10, 20]);
+        assert!(errors(&input, Limits::default()).is_empty());
+    }
+
+    #[test]
+    fn bad_account_is_rejected() {
+        let input = batch_mint("not an account", vec![1], vec![10]);
+        let errors = errors(&input, Limits::default());
+        assert_eq!(fields(&errors), ["to"]);
+        assert_eq!(errors[0].reason, "invalid account");
+    }
+
+    #[test]
+    fn bad_account_in_list_is_rejected_by_index() {
+        let input = bag::CreateInput {
+            signer: Signer::default(),
+            tx_options: None,
+            class_id: ClassId::from(1),
+            owners: vec![
+                Account::from(ALICE.to_string()),
+                Account::from("0x1234".to_string()),
+            ],
+            shares: vec![Balance::from(1), Balance::from(1)],
+        };
+        assert_eq!(fields(&errors(&input, Limits::default())), ["owners[1]"]);
+    }
+
+    #[test]
+    fn empty_vectors_are_rejected() {
+        let input = batch_mint(ALICE, vec![], vec![]);
+        let errors = errors(&input, Limits::default());
+        assert_eq!(fields(&errors), ["asset_ids"]);
+        assert_eq!(errors[0].reason, "must not be empty");
+    }
+
+    #[test]
+    fn length_mismatch_is_rejected() {
+        let input = batch_mint(ALICE, vec![1, 2], vec![10]);
+        let errors = errors(&input, Limits::default());
+        assert_eq!(fields(&errors), ["amounts"]);
+        assert_eq!(errors[0].reason, "has length 1 but asset_ids has length 2");
+    }
+
+    #[test]
+    fn too_many_items_are_rejected() {
+        let input = bag::CreateInput {
+            signer: Signer::default(),
+            tx_options: None,
+            class_id: ClassId::from(1),
+            owners: vec![Account::from(ALICE.to_string()); 3],
+            shares: vec![Balance::from(1); 3],
+        };
+        let limits = Limits {
+            max_bag_owners: Some(2),
+            ..Limits::default()
+        };
+        let errors = errors(&input, limits);
+        assert_eq!(fields(&errors), ["owners"]);
+        assert_eq!(errors[0].reason, "has length 3, at most 2");
+    }
+
+    #[test]
+    fn nested_length_mismatch_is_rejected_by_index() {
+        let input = bag::DepositInput {
+            signer: Signer::default(),
+            tx_options: None,
+            bag: Account::from(ALICE.to_string()),
+            class_ids: vec![ClassId::from(1), ClassId::from(2)],
+            asset_ids: vec![
+                vec![AssetId::from(1)],
+                vec![AssetId::from(1), AssetId::from(2)],
+            ],
+            amounts: vec![vec![Balance::from(10)], vec![Balance::from(10)]],
+        };
+        let errors = errors(&input, Limits::default());
+        assert_eq!(fields(&errors), ["amounts[1]"]);
+        assert_eq!(
+            errors[0].reason,
+            "has length 1 but asset_ids[1] has length 2"
+        );
+    }
+
+    #[test]
+    fn bundle_schema_fields_are_prefixed() {
+        let input = RegisterBundleInput {
+            signer: Signer::default(),
+            tx_options: None,
+            class_id: ClassId::from(1),
+            asset_id: AssetId::from(1),
+            schema: BundleSchema {
+                class_ids: vec![ClassId::from(1)],
+                asset_ids: vec![vec![AssetId::from(1), AssetId::from(2), AssetId::from(3)]],
+                amounts: vec![vec![Balance::from(10); 3], vec![]],
+            },
+            metadata: json!({}),
+        };
+        let limits = Limits {
+            max_bundle_assets: Some(2),
+            ..Limits::default()
+        };
+        let errors = errors(&input, limits);
+        assert_eq!(fields(&errors), ["schema.amounts", "schema.asset_ids[0]"]);
+        assert_eq!(
+            errors[0].reason,
+            "has length 2 but schema.class_ids has length 1"
+        );
+        assert_eq!(errors[1].reason, "has length 3, at most 2");
+    }
+
+    #[test]
+    fn metadata_size_is_limited() {
+        let metadata = json!({"name": "a long enough name"});
+        let size = serde_json::to_vec(&metadata).unwrap().len() as u32;
+
+        let at_limit = Limits {
+            max_class_metadata: Some(size),
+            ..Limits::default()
+        };
+        assert!(errors(&create_class(metadata.clone()), at_limit).is_empty());
+
+        let below = Limits {
+            max_class_metadata: Some(size - 1),
+            ..Limits::default()
+        };
+        let errors = errors(&create_class(metadata), below);
+        assert_eq!(fields(&errors), ["metadata"]);
+        assert_eq!(
+            errors[0].reason,
+            format!("is {} bytes once encoded, at most {}", size, size - 1)
+        );
+    }
+
+    #[test]
+    fn metadata_is_unlimited_without_constant() {
+        let metadata = json!({"name": "x".repeat(10_000)});
+        assert!(errors(&create_class(metadata), Limits::default()).is_empty());
+    }
+}
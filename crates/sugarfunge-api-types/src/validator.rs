@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AddValidatorInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub validator_id: ValidatorId,
 }
 
@@ -14,7 +16,9 @@ pub struct AddValidatorOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RemoveValidatorInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub validator_id: ValidatorId,
 }
 
@@ -25,7 +29,9 @@ pub struct RemoveValidatorOutput {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SetKeysInput {
-    pub seed: Seed,
+    #[serde(flatten)]
+    pub signer: Signer,
+    pub tx_options: Option<TxOptions>,
     pub aura: String,
     pub grandpa: String,
 }
@@ -1,38 +1,57 @@
 use crate::state::*;
+use crate::sudo::check_sudid;
+use crate::tx::*;
 use crate::util::*;
-use actix_web::{error, web, HttpRequest, HttpResponse};
+use actix_web::{error, web, HttpResponse};
 use rand::prelude::*;
 use serde_json::json;
-use subxt::ext::sp_core::sr25519::Public as SubxtPublic;
-use subxt::ext::sp_core::Pair;
-use subxt::ext::sp_runtime::traits::IdentifyAccount;
-use subxt::tx::PairSigner;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::ext::sp_core::sr25519::Pair as Sr25519Pair;
+use subxt::tx::Signer as _;
+use subxt::PolkadotConfig;
 use sugarfunge_api_types::account::*;
 use sugarfunge_api_types::primitives::*;
 use sugarfunge_api_types::sugarfunge;
 
-/// Generate a unique seed and its associated account
-pub async fn create(_req: HttpRequest) -> error::Result<HttpResponse> {
-    let seed = rand::thread_rng().gen::<[u8; 32]>();
-    let seed = hex::encode(seed);
-    let seed = format!("//{}", seed);
-    let seed = Seed::from(seed);
-    let pair = get_pair_from_seed(&seed)?;
-    let account: SubxtPublic = pair.public();
-    let account = account.into_account();
+/// Generate a unique seed, or a BIP39 mnemonic, and its associated account
+pub async fn create(req: Option<web::Json<CreateAccountInput>>) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let seed = if req.mnemonic {
+        let (_, phrase, _) = Sr25519Pair::generate_with_phrase(None);
+        Seed::from(phrase)
+    } else {
+        let seed = rand::thread_rng().gen::<[u8; 32]>();
+        let seed = hex::encode(seed);
+        let seed = format!("//{}", seed);
+        Seed::from(seed)
+    };
+    let signer = get_signer_from_suri(
+        &seed,
+        req.derivation_path.as_deref(),
+        req.password.as_deref(),
+        req.scheme.unwrap_or_default(),
+    )?;
     Ok(HttpResponse::Ok().json(CreateAccountOutput {
         seed,
-        account: Account::from(format!("{}", account)),
+        derivation_path: req.derivation_path,
+        public_key: format!("0x{}", hex::encode(signer.public_key())),
+        account: signer.account_id().into(),
     }))
 }
 
-/// Compute account from seed
+/// Compute account from seed and optional derivation path
 pub async fn seeded(req: web::Json<SeededAccountInput>) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let account = pair.public().into_account();
+    let signer = get_signer_from_suri(
+        &req.seed,
+        req.derivation_path.as_deref(),
+        req.password.as_deref(),
+        req.scheme.unwrap_or_default(),
+    )?;
     Ok(HttpResponse::Ok().json(SeededAccountOutput {
         seed: req.seed.clone(),
-        account: Account::from(format!("{}", account)),
+        derivation_path: req.derivation_path.clone(),
+        public_key: format!("0x{}", hex::encode(signer.public_key())),
+        account: signer.account_id().into(),
     }))
 }
 
@@ -41,38 +60,51 @@ pub async fn fund(
     data: web::Data<AppState>,
     req: web::Json<FundAccountInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed).unwrap(); // Assuming get_pair_from_seed now returns the correct type
-    let signer = PairSigner::new(pair);
-    let account = subxt::utils::AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let account = subxt::utils::MultiAddress::Id(account);
-    let amount_input = req.amount;
-    let api = &data.api;
-
-    let call = sugarfunge::tx()
-        .balances()
-        .transfer_keep_alive(account, amount_input.into());
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::balances::events::Transfer>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(FundAccountOutput {
-            from: event.from.into(),
-            to: event.to.into(),
-            amount: event.amount.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::balances::events::Transfer"),
-            description: "Error in account::fund".to_string(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for FundAccountInput {
+    type Output = FundAccountOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let account = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+        let account = subxt::utils::MultiAddress::Id(account);
+        let amount_input = self.amount;
+
+        let call = sugarfunge::tx()
+            .balances()
+            .transfer_keep_alive(account, amount_input.into());
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<FundAccountOutput> {
+        let result = events
+            .find_first::<sugarfunge::balances::events::Transfer>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(FundAccountOutput {
+                from: event.from.into(),
+                to: event.to.into(),
+                amount: event.amount.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::balances::events::Transfer"),
+                description: "Error in account::fund".to_string(),
+            })),
+        }
     }
 }
 
@@ -82,16 +114,14 @@ pub async fn balance(
     req: web::Json<AccountBalanceInput>,
 ) -> error::Result<HttpResponse> {
     let account = subxt::utils::AccountId32::try_from(&req.account).map_err(map_account_err)?;
-    let api = &data.api;
-
     let call = sugarfunge::storage().system().account(&account);
 
-    //let result = api.storage().fetch(&call, None).await;
-    let block = api.blocks().at_latest().await.unwrap();
-    let data = block.storage().fetch(&call).await.unwrap();
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let data = block.storage().fetch(&call).await.map_err(map_subxt_err)?;
     match data {
         Some(data) => Ok(HttpResponse::Ok().json(AccountBalanceOutput {
             balance: data.data.free.into(),
+            block: at_block(&block),
         })),
         None => Ok(HttpResponse::BadRequest().json(RequestError {
             message: json!("Failed to find sugarfunge::balances::events::balance"),
@@ -107,20 +137,21 @@ pub async fn exists(
 ) -> error::Result<HttpResponse> {
     let account = subxt::utils::AccountId32::try_from(&req.account).map_err(map_account_err)?;
     let account_out = account.clone();
-    let api = &data.api;
 
     let call = sugarfunge::storage().system().account(&account);
 
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
-    let data = storage.fetch(&call).await.map_err(map_subxt_err)?;
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let data = block.storage().fetch(&call).await.map_err(map_subxt_err)?;
     match data {
         Some(data) => Ok(HttpResponse::Ok().json(AccountExistsOutput {
             account: account_out.into(),
             exists: data.providers > 0,
+            block: at_block(&block),
         })),
         None => Ok(HttpResponse::Ok().json(AccountExistsOutput {
             account: account_out.into(),
             exists: false,
+            block: at_block(&block),
         })),
     }
 }
@@ -130,41 +161,55 @@ pub async fn set_balance(
     data: web::Data<AppState>,
     req: web::Json<SetBalanceInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed).unwrap(); // Assuming get_pair_from_seed now returns the correct type
-    let signer = PairSigner::new(pair);
-    let account = subxt::utils::AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let account = subxt::utils::MultiAddress::Id(account);
-    let amount_input = req.amount;
-    let api = &data.api;
-
-    let call = sugarfunge::runtime_types::pallet_balances::pallet::Call::force_set_balance {
-        who: account,
-        new_free: amount_input.into(),
-    };
+    execute(&data, req.into_inner()).await
+}
 
-    let call = sugarfunge::runtime_types::sugarfunge_runtime::RuntimeCall::Balances(call);
-
-    let sudo_call = sugarfunge::tx().sudo().sudo(call);
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&sudo_call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::balances::events::BalanceSet>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(SetBalanceOutput {
-            account: event.who.into(),
-            amount: event.free.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::balances::events::BalanceSet"),
-            description: "Error in account::set_balance".to_string(),
-        })),
+impl Operation for SetBalanceInput {
+    type Output = SetBalanceOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let account = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+        let account = subxt::utils::MultiAddress::Id(account);
+        let amount_input = self.amount;
+
+        let call = sugarfunge::runtime_types::pallet_balances::pallet::Call::force_set_balance {
+            who: account,
+            new_free: amount_input.into(),
+        };
+
+        let call = sugarfunge::runtime_types::sugarfunge_runtime::RuntimeCall::Balances(call);
+
+        let sudo_call = sugarfunge::tx().sudo().sudo(call);
+
+        api.tx().call_data(&sudo_call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<SetBalanceOutput> {
+        check_sudid(events)?;
+        let result = events
+            .find_first::<sugarfunge::balances::events::BalanceSet>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(SetBalanceOutput {
+                account: event.who.into(),
+                amount: event.free.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::balances::events::BalanceSet"),
+                description: "Error in account::set_balance".to_string(),
+            })),
+        }
     }
 }
@@ -1,4 +1,5 @@
 use clap::Parser;
+use std::path::PathBuf;
 // use structopt::StructOpt;
 use url::Url;
 
@@ -11,4 +12,19 @@ pub struct Args {
     pub listen: Url,
     #[arg(short, long = "db-uri", value_parser)]
     pub db: Option<String>,
+    #[arg(short, long, value_parser, default_value = "keystore")]
+    pub keystore: PathBuf,
+    #[arg(long, env = "SUGARFUNGE_KEYSTORE_PASSPHRASE", hide_env_values = true)]
+    pub keystore_passphrase: Option<String>,
+    /// Bearer token of the keystore endpoints, which are disabled without it
+    #[arg(long, env = "SUGARFUNGE_KEYSTORE_TOKEN", hide_env_values = true)]
+    pub keystore_token: Option<String>,
+    /// Allow operations to be signed with keystore keys
+    #[arg(long)]
+    pub keystore_signing: bool,
+    #[arg(long, value_parser, default_value = "jobs")]
+    pub jobs: PathBuf,
+    /// Seconds during which requests with the same Idempotency-Key are replayed
+    #[arg(long, value_parser, default_value_t = 24 * 60 * 60)]
+    pub idempotency_retention: u64,
 }
@@ -1,54 +1,69 @@
 use crate::state::*;
+use crate::storage::{fetch_values, key_cursor, page_limit, KeyDecoder, KeyPages};
+use crate::tx::*;
 use crate::util::*;
 use actix_web::{error, web, HttpResponse};
-use codec::Decode;
 use futures::stream::StreamExt;
 use serde_json::json;
+use std::collections::HashMap;
 use std::str::FromStr;
-use subxt::tx::PairSigner;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::PolkadotConfig;
 use sugarfunge_api_types::asset::*;
 use sugarfunge_api_types::primitives::*;
 use sugarfunge_api_types::sugarfunge;
 use sugarfunge_api_types::sugarfunge::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+use sugarfunge_api_types::validation::FieldError;
 
 /// Create an asset class for an account
 pub async fn create_class(
     data: web::Data<AppState>,
     req: web::Json<CreateClassInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let to = sp_core::sr25519::Public::from_str(req.owner.as_str()).map_err(map_account_err)?;
-    let to_array: [u8; 32] = to.0; // Convert 'Public' to array
-    let to = subxt::utils::AccountId32::from(to_array);
-    let metadata = serde_json::to_vec(&req.metadata).unwrap_or_default();
-    let metadata = BoundedVec(metadata);
-    let api = &data.api;
-
-    let call = sugarfunge::tx()
-        .asset()
-        .create_class(to.into(), req.class_id.into(), metadata);
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::asset::events::ClassCreated>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(CreateClassOutput {
-            class_id: event.class_id.into(),
-            who: event.who.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::asset::events::ClassCreated"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for CreateClassInput {
+    type Output = CreateClassOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let to = subxt::utils::AccountId32::try_from(&self.owner).map_err(map_account_err)?;
+        let metadata = metadata_bytes(&self.metadata)?;
+        let metadata = BoundedVec(metadata);
+
+        let call = sugarfunge::tx()
+            .asset()
+            .create_class(to.into(), self.class_id.into(), metadata);
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CreateClassOutput> {
+        let result = events
+            .find_first::<sugarfunge::asset::events::ClassCreated>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(CreateClassOutput {
+                class_id: event.class_id.into(),
+                who: event.who.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::ClassCreated"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -57,65 +72,174 @@ pub async fn class_info(
     data: web::Data<AppState>,
     req: web::Json<ClassInfoInput>,
 ) -> error::Result<HttpResponse> {
-    let api = &data.api;
-
     let call = sugarfunge::storage()
         .asset()
         .classes(u64::from(req.class_id));
 
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
-
-    let info = storage.fetch(&call).await.map_err(map_subxt_err)?;
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let info = block.storage().fetch(&call).await.map_err(map_subxt_err)?;
 
     Ok(HttpResponse::Ok().json(ClassInfoOutput {
         info: match info {
-            Some(info) => Some(ClassInfo {
-                class_id: req.class_id,
-                owner: info.owner.into(),
-                metadata: serde_json::from_slice(info.metadata.0.as_slice()).unwrap_or_default(),
-            }),
+            Some(info) => {
+                let metadata_changed_at =
+                    metadata_changed_at(&data, req.class_id, &info.metadata.0, block.number())
+                        .await;
+                Some(ClassInfo {
+                    class_id: req.class_id,
+                    owner: info.owner.into(),
+                    metadata: serde_json::from_slice(info.metadata.0.as_slice())
+                        .unwrap_or_default(),
+                    metadata_changed_at,
+                })
+            }
             None => None,
         },
+        block: at_block(&block),
     }))
 }
 
+/// Block from which a class has had its current metadata, found by
+/// bisecting the history of the class storage up to the given block.
+/// The block found holds the current metadata and the one before it does
+/// not, it is the latest such block unless the metadata was set back to
+/// an earlier value. `None` when the node pruned the state of past blocks.
+async fn metadata_changed_at(
+    data: &AppState,
+    class_id: ClassId,
+    metadata: &[u8],
+    latest: u32,
+) -> Option<u64> {
+    let call = sugarfunge::storage().asset().classes(u64::from(class_id));
+    let (mut low, mut high) = (0, latest);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        let hash = data
+            .rpc
+            .chain_get_block_hash(Some(middle.into()))
+            .await
+            .ok()??;
+        let class = data.api.storage().at(hash).fetch(&call).await.ok()?;
+        if class.map_or(false, |class| class.metadata.0 == metadata) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Some(high.into())
+}
+
+/// Update the metadata of an asset class
+pub async fn update_class_metadata(
+    data: web::Data<AppState>,
+    req: web::Json<UpdateClassMetadataInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for UpdateClassMetadataInput {
+    type Output = ClassMetadataUpdatedOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let metadata = metadata_bytes(&self.metadata)?;
+        let metadata = BoundedVec(metadata);
+
+        let call = sugarfunge::tx()
+            .asset()
+            .update_class_metadata(self.class_id.into(), metadata);
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<ClassMetadataUpdatedOutput> {
+        // No event is emitted, the metadata is read back at the block
+        let call = sugarfunge::storage()
+            .asset()
+            .classes(u64::from(self.class_id));
+        let info = data
+            .api
+            .storage()
+            .at(events.block_hash())
+            .fetch(&call)
+            .await
+            .map_err(map_subxt_err)?
+            .ok_or_else(|| {
+                map_request_err(RequestError {
+                    message: json!("Class not found after updating its metadata"),
+                    description: String::new(),
+                })
+            })?;
+        Ok(ClassMetadataUpdatedOutput {
+            class_id: self.class_id,
+            metadata: serde_json::from_slice(info.metadata.0.as_slice()).unwrap_or_default(),
+            block_hash: format!("{:?}", events.block_hash()),
+        })
+    }
+}
+
 /// Create an asset for class
 pub async fn create(
     data: web::Data<AppState>,
     req: web::Json<CreateInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let metadata: Vec<u8> = serde_json::to_vec(&req.metadata).unwrap_or_default();
-    let metadata = BoundedVec(metadata);
-    let api = &data.api;
-
-    let call =
-        sugarfunge::tx()
-            .asset()
-            .create_asset(req.class_id.into(), req.asset_id.into(), metadata);
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::asset::events::AssetCreated>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(CreateOutput {
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-            who: event.who.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::asset::events::ClassCreated"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for CreateInput {
+    type Output = CreateOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let metadata: Vec<u8> = metadata_bytes(&self.metadata)?;
+        let metadata = BoundedVec(metadata);
+
+        let call = sugarfunge::tx().asset().create_asset(
+            self.class_id.into(),
+            self.asset_id.into(),
+            metadata,
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CreateOutput> {
+        let result = events
+            .find_first::<sugarfunge::asset::events::AssetCreated>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(CreateOutput {
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+                who: event.who.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::ClassCreated"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -124,15 +248,12 @@ pub async fn info(
     data: web::Data<AppState>,
     req: web::Json<AssetInfoInput>,
 ) -> error::Result<HttpResponse> {
-    let api = &data.api;
-
     let call = sugarfunge::storage()
         .asset()
         .assets(u64::from(req.class_id), u64::from(req.asset_id));
 
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
-
-    let info = storage.fetch(&call).await.map_err(map_subxt_err)?;
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let info = block.storage().fetch(&call).await.map_err(map_subxt_err)?;
 
     Ok(HttpResponse::Ok().json(AssetInfoOutput {
         info: match info {
@@ -143,6 +264,185 @@ pub async fn info(
             }),
             None => None,
         },
+        block: at_block(&block),
+    }))
+}
+
+fn owner_filter(owner: Option<&Account>) -> error::Result<Option<subxt::utils::AccountId32>> {
+    owner
+        .map(subxt::utils::AccountId32::try_from)
+        .transpose()
+        .map_err(map_account_err)
+}
+
+/// Storage keys read by one listing request at most, a page filtered on
+/// an owner may end short of `limit` with a cursor to resume from
+const MAX_SCANNED_KEYS: usize = 10_000;
+
+/// Next keys of a listing, as many as a page holds
+async fn next_keys(keys: &mut KeyPages<'_>, limit: u32) -> error::Result<Vec<Vec<u8>>> {
+    let mut batch = Vec::new();
+    while batch.len() < limit as usize {
+        match keys.next().await? {
+            Some(key) => batch.push(key),
+            None => break,
+        }
+    }
+    Ok(batch)
+}
+
+/// List asset classes, a page at a time
+pub async fn classes(
+    data: web::Data<AppState>,
+    req: Option<web::Json<ClassesInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let owner = owner_filter(req.owner.as_ref())?;
+    let limit = page_limit(req.limit);
+
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let address = sugarfunge::storage().asset().classes_iter();
+    let prefix = data
+        .api
+        .storage()
+        .address_bytes(&address)
+        .map_err(map_subxt_err)?;
+    let mut keys = KeyPages::new(&data, prefix, block.hash(), req.cursor.as_deref(), limit)?;
+    let decoder = KeyDecoder::new(&data.api.metadata(), "Asset", "Classes")?;
+
+    let mut classes = Vec::new();
+    let mut next_cursor = None;
+    let mut scanned = 0;
+    'pages: loop {
+        let batch = next_keys(&mut keys, limit).await?;
+        if batch.is_empty() {
+            break;
+        }
+        let values = fetch_values(&data, block.hash(), &address, &batch).await?;
+        for (key, info) in batch.iter().zip(values) {
+            scanned += 1;
+            let class_id: u64 = decoder.keys(key)?.next()?;
+            if let Some(info) = info {
+                if owner.as_ref().map_or(true, |owner| *owner == info.owner) {
+                    classes.push(ClassItem {
+                        class_id: class_id.into(),
+                        owner: info.owner.into(),
+                        metadata: serde_json::from_slice(info.metadata.0.as_slice())
+                            .unwrap_or_default(),
+                    });
+                }
+            }
+            if classes.len() == limit as usize || scanned == MAX_SCANNED_KEYS {
+                next_cursor = Some(key_cursor(key));
+                break 'pages;
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ClassesOutput {
+        classes,
+        next_cursor,
+        block: at_block(&block),
+    }))
+}
+
+/// List assets, of all classes or of one, a page at a time
+pub async fn list(
+    data: web::Data<AppState>,
+    req: Option<web::Json<AssetsInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let owner = owner_filter(req.owner.as_ref())?;
+    let limit = page_limit(req.limit);
+
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let address = sugarfunge::storage().asset().assets_iter();
+    let prefix = match req.class_id {
+        Some(class_id) => data.api.storage().address_bytes(
+            &sugarfunge::storage()
+                .asset()
+                .assets_iter1(u64::from(class_id)),
+        ),
+        None => data.api.storage().address_bytes(&address),
+    }
+    .map_err(map_subxt_err)?;
+    let mut keys = KeyPages::new(&data, prefix, block.hash(), req.cursor.as_deref(), limit)?;
+    let decoder = KeyDecoder::new(&data.api.metadata(), "Asset", "Assets")?;
+    let classes_address = sugarfunge::storage().asset().classes_iter();
+
+    // Whether each class seen so far is owned by the owner filtered on
+    let mut owned_classes = HashMap::new();
+    let mut assets = Vec::new();
+    let mut next_cursor = None;
+    let mut scanned = 0;
+    'pages: loop {
+        let batch = next_keys(&mut keys, limit).await?;
+        if batch.is_empty() {
+            break;
+        }
+        let ids = batch
+            .iter()
+            .map(|key| {
+                let mut ids = decoder.keys(key)?;
+                Ok((ids.next::<u64>()?, ids.next::<u64>()?))
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+
+        if let Some(owner) = &owner {
+            let mut class_ids: Vec<u64> = ids
+                .iter()
+                .map(|(class_id, _)| *class_id)
+                .filter(|class_id| !owned_classes.contains_key(class_id))
+                .collect();
+            class_ids.sort_unstable();
+            class_ids.dedup();
+            let class_keys = class_ids
+                .iter()
+                .map(|class_id| {
+                    data.api
+                        .storage()
+                        .address_bytes(&sugarfunge::storage().asset().classes(*class_id))
+                        .map_err(map_subxt_err)
+                })
+                .collect::<error::Result<Vec<_>>>()?;
+            let classes = fetch_values(&data, block.hash(), &classes_address, &class_keys).await?;
+            for (class_id, class) in class_ids.into_iter().zip(classes) {
+                owned_classes.insert(class_id, class.map_or(false, |class| class.owner == *owner));
+            }
+        }
+
+        // Only the assets of owned classes are read
+        let wanted: Vec<usize> = (0..batch.len())
+            .filter(|index| owned_classes.get(&ids[*index].0).copied().unwrap_or(true))
+            .collect();
+        let wanted_keys: Vec<Vec<u8>> = wanted.iter().map(|index| batch[*index].clone()).collect();
+        let mut values: HashMap<usize, _> = wanted
+            .into_iter()
+            .zip(fetch_values(&data, block.hash(), &address, &wanted_keys).await?)
+            .collect();
+
+        for (index, key) in batch.iter().enumerate() {
+            scanned += 1;
+            let (class_id, asset_id) = ids[index];
+            if let Some(Some(info)) = values.remove(&index) {
+                assets.push(AssetInfo {
+                    class_id: class_id.into(),
+                    asset_id: asset_id.into(),
+                    metadata: serde_json::from_slice(info.metadata.0.as_slice())
+                        .unwrap_or_default(),
+                });
+            }
+            if assets.len() == limit as usize || scanned == MAX_SCANNED_KEYS {
+                next_cursor = Some(key_cursor(key));
+                break 'pages;
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(AssetsOutput {
+        assets,
+        next_cursor,
+        block: at_block(&block),
     }))
 }
 
@@ -151,40 +451,53 @@ pub async fn update_metadata(
     data: web::Data<AppState>,
     req: web::Json<UpdateMetadataInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let metadata = serde_json::to_vec(&req.metadata).unwrap_or_default();
-    let metadata = BoundedVec(metadata);
-    let api = &data.api;
-
-    let call = sugarfunge::tx().asset().update_asset_metadata(
-        req.class_id.into(),
-        req.asset_id.into(),
-        metadata,
-    );
+    execute(&data, req.into_inner()).await
+}
 
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::asset::events::AssetMetadataUpdated>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(UpdateMetadataOutput {
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-            who: event.who.into(),
-            metadata: serde_json::from_slice(event.metadata.as_slice()).unwrap_or_default(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::asset::events::ClassCreated"),
-            description: String::new(),
-        })),
+impl Operation for UpdateMetadataInput {
+    type Output = UpdateMetadataOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let metadata = metadata_bytes(&self.metadata)?;
+        let metadata = BoundedVec(metadata);
+
+        let call = sugarfunge::tx().asset().update_asset_metadata(
+            self.class_id.into(),
+            self.asset_id.into(),
+            metadata,
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<UpdateMetadataOutput> {
+        let result = events
+            .find_first::<sugarfunge::asset::events::AssetMetadataUpdated>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(UpdateMetadataOutput {
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+                who: event.who.into(),
+                metadata: serde_json::from_slice(event.metadata.as_slice()).unwrap_or_default(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::ClassCreated"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -193,41 +506,54 @@ pub async fn mint(
     data: web::Data<AppState>,
     req: web::Json<MintInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let to = subxt::utils::AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let api = &data.api;
-
-    let call = sugarfunge::tx().asset().mint(
-        to,
-        req.class_id.into(),
-        req.asset_id.into(),
-        req.amount.into(),
-    );
+    execute(&data, req.into_inner()).await
+}
 
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::asset::events::Mint>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(MintOutput {
-            to: event.to.into(),
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-            amount: event.amount.into(),
-            who: event.who.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::currency::events::AssetMint"),
-            description: String::new(),
-        })),
+impl Operation for MintInput {
+    type Output = MintOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let to = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().asset().mint(
+            to,
+            self.class_id.into(),
+            self.asset_id.into(),
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<MintOutput> {
+        let result = events
+            .find_first::<sugarfunge::asset::events::Mint>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(MintOutput {
+                to: event.to.into(),
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+                amount: event.amount.into(),
+                who: event.who.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::currency::events::AssetMint"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -236,73 +562,196 @@ pub async fn burn(
     data: web::Data<AppState>,
     req: web::Json<BurnInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let from = subxt::utils::AccountId32::try_from(&req.from).map_err(map_account_err)?;
-    let api = &data.api;
-
-    let call = sugarfunge::tx().asset().burn(
-        from,
-        req.class_id.into(),
-        req.asset_id.into(),
-        req.amount.into(),
-    );
+    execute(&data, req.into_inner()).await
+}
 
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::asset::events::Burn>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(BurnOutput {
-            from: event.from.into(),
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-            amount: event.amount.into(),
-            who: event.who.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::currency::events::Burn"),
-            description: String::new(),
-        })),
+impl Operation for BurnInput {
+    type Output = BurnOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let from = subxt::utils::AccountId32::try_from(&self.from).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().asset().burn(
+            from,
+            self.class_id.into(),
+            self.asset_id.into(),
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<BurnOutput> {
+        let result = events
+            .find_first::<sugarfunge::asset::events::Burn>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(BurnOutput {
+                from: event.from.into(),
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+                amount: event.amount.into(),
+                who: event.who.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::currency::events::Burn"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
-/// Get balance for given asset
-pub async fn balance(
+/// Mint amounts of several assets of a class to account
+pub async fn batch_mint(
     data: web::Data<AppState>,
-    req: web::Json<AssetBalanceInput>,
+    req: web::Json<BatchMintInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for BatchMintInput {
+    type Output = BatchMintOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let to = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().asset().batch_mint(
+            to,
+            self.class_id.into(),
+            transform_vec_assetid_to_u64(self.asset_ids.clone()),
+            transform_vec_balance_to_u128(&self.amounts),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<BatchMintOutput> {
+        let events = events
+            .find::<sugarfunge::asset::events::BatchMint>()
+            .map(|event| {
+                event.map(|event| BatchMintItemOutput {
+                    to: event.to.into(),
+                    class_id: event.class_id.into(),
+                    asset_ids: event.asset_ids.into_iter().map(AssetId::from).collect(),
+                    amounts: event.amounts.into_iter().map(Balance::from).collect(),
+                    who: event.who.into(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(map_subxt_err)?;
+        if events.is_empty() {
+            return Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::BatchMint"),
+                description: String::new(),
+            }));
+        }
+        Ok(BatchMintOutput { events })
+    }
+}
+
+/// Burn amounts of several assets of a class from account
+pub async fn batch_burn(
+    data: web::Data<AppState>,
+    req: web::Json<BatchBurnInput>,
 ) -> error::Result<HttpResponse> {
-    // Convert the provided account public key from a string to sp_core::sr25519::Public
-    let account_public =
-        sp_core::sr25519::Public::from_str(req.account.as_str()).map_err(map_account_err)?;
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for BatchBurnInput {
+    type Output = BatchBurnOutput;
 
-    // Convert sp_core::sr25519::Public to a [u8; 32] array
-    let account_array: [u8; 32] = account_public.0;
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
 
-    // Convert [u8; 32] array to subxt::utils::AccountId32
-    let account = subxt::utils::AccountId32::from(account_array);
-    let api = &data.api;
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
 
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let from = subxt::utils::AccountId32::try_from(&self.from).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().asset().batch_burn(
+            from,
+            self.class_id.into(),
+            transform_vec_assetid_to_u64(self.asset_ids.clone()),
+            transform_vec_balance_to_u128(&self.amounts),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<BatchBurnOutput> {
+        let events = events
+            .find::<sugarfunge::asset::events::BatchBurn>()
+            .map(|event| {
+                event.map(|event| BatchBurnItemOutput {
+                    from: event.from.into(),
+                    class_id: event.class_id.into(),
+                    asset_ids: event.asset_ids.into_iter().map(AssetId::from).collect(),
+                    amounts: event.amounts.into_iter().map(Balance::from).collect(),
+                    who: event.who.into(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(map_subxt_err)?;
+        if events.is_empty() {
+            return Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::BatchBurn"),
+                description: String::new(),
+            }));
+        }
+        Ok(BatchBurnOutput { events })
+    }
+}
+
+/// Get balance for given asset
+pub async fn balance(
+    data: web::Data<AppState>,
+    req: web::Json<AssetBalanceInput>,
+) -> error::Result<HttpResponse> {
+    let account = subxt::utils::AccountId32::try_from(&req.account).map_err(map_account_err)?;
     let call = sugarfunge::storage().asset().balances(
         &account,
         u64::from(req.class_id),
         u64::from(req.asset_id),
     );
 
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
-
-    let amount = storage.fetch(&call).await.map_err(map_subxt_err)?;
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let amount = block.storage().fetch(&call).await.map_err(map_subxt_err)?;
 
     match amount {
         Some(amount) => Ok(HttpResponse::Ok().json(AssetBalanceOutput {
             amount: amount.into(),
+            block: at_block(&block),
         })),
         None => Ok(HttpResponse::BadRequest().json(RequestError {
             message: json!("Failed to find sugarfunge::balances::events::balance"),
@@ -311,106 +760,154 @@ pub async fn balance(
     }
 }
 
+fn balance_item(
+    decoder: &KeyDecoder,
+    key: &[u8],
+    amount: u128,
+) -> error::Result<AssetBalanceItemOutput> {
+    let mut keys = decoder.keys(key)?;
+    let _account: subxt::utils::AccountId32 = keys.next()?;
+    let class_id: u64 = keys.next()?;
+    let asset_id: u64 = keys.next()?;
+    Ok(AssetBalanceItemOutput {
+        class_id: class_id.into(),
+        asset_id: asset_id.into(),
+        amount: amount.into(),
+    })
+}
+
 /// Get balances for owner and maybe class
 pub async fn balances(
     data: web::Data<AppState>,
     req: web::Json<AssetBalancesInput>,
 ) -> error::Result<HttpResponse> {
-    // Convert the provided account public key from a string to sp_core::sr25519::Public
-    let account_public =
-        sp_core::sr25519::Public::from_str(&req.account.as_str()).map_err(map_account_err)?;
+    let account = subxt::utils::AccountId32::try_from(&req.account).map_err(map_account_err)?;
+    let decoder = KeyDecoder::new(&data.api.metadata(), "Asset", "Balances")?;
 
-    // Convert sp_core::sr25519::Public to a [u8; 32] array
-    let account_array: [u8; 32] = account_public.0;
+    // Keys and values are read together, a page at a time, from the same block
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let storage = block.storage();
+    let asset = sugarfunge::storage().asset();
 
-    // Convert [u8; 32] array to subxt::utils::AccountId32
-    let account = subxt::utils::AccountId32::from(account_array);
-    let api = &data.api;
-
-    let account_clone = account.clone();
     let mut result_array = Vec::new();
-
-    let query_key: Vec<u8>;
-    // println!("query_key manifests_root len: {}", query_key.len());
-
-    if let Some(class_id) = req.class_id.clone() {
-        let class_id_u64: u64 = class_id.into();
-        if let Some(asset_id) = req.asset_id.clone() {
-            let asset_id_u64: u64 = asset_id.into();
-            query_key = sugarfunge::storage()
-                .asset()
-                .balances(account_clone, class_id_u64, asset_id_u64)
-                .to_root_bytes();
-        } else {
-            query_key = sugarfunge::storage()
-                .asset()
-                .balances_iter2(account_clone, class_id_u64)
-                .to_root_bytes();
+    match (req.class_id, req.asset_id) {
+        (Some(class_id), Some(asset_id)) => {
+            let call = asset.balances(&account, u64::from(class_id), u64::from(asset_id));
+            if let Some(amount) = storage.fetch(&call).await.map_err(map_subxt_err)? {
+                result_array.push(AssetBalanceItemOutput {
+                    class_id,
+                    asset_id,
+                    amount: amount.into(),
+                });
+            }
+        }
+        (Some(class_id), None) => {
+            let call = asset.balances_iter2(&account, u64::from(class_id));
+            let mut pairs = storage.iter(call).await.map_err(map_subxt_err)?;
+            while let Some(pair) = pairs.next().await {
+                let pair = pair.map_err(map_subxt_err)?;
+                result_array.push(balance_item(&decoder, &pair.key_bytes, pair.value)?);
+            }
+        }
+        (None, _) => {
+            let call = asset.balances_iter1(&account);
+            let mut pairs = storage.iter(call).await.map_err(map_subxt_err)?;
+            while let Some(pair) = pairs.next().await {
+                let pair = pair.map_err(map_subxt_err)?;
+                result_array.push(balance_item(&decoder, &pair.key_bytes, pair.value)?);
+            }
         }
-    } else {
-        query_key = sugarfunge::storage()
-            .asset()
-            .balances_iter1(account_clone)
-            .to_root_bytes();
     }
 
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
+    Ok(HttpResponse::Ok().json(AssetBalancesOutput {
+        balances: result_array,
+        block: at_block(&block),
+    }))
+}
 
-    let keys_stream = storage
-        .fetch_raw_keys(query_key)
-        .await
-        .map_err(map_subxt_err)?;
+/// Cursor of a page of holders, the amount and account of the last holder
+fn holder_cursor(account: &subxt::utils::AccountId32, amount: u128) -> String {
+    format!("{}:{}", amount, account)
+}
 
-    let keys: Vec<Vec<u8>> = keys_stream
-        .collect::<Vec<_>>() // Collect into a Vec<Result<Vec<u8>, Error>>
-        .await // Await the collection process
-        .into_iter() // Convert into an iterator
-        .filter_map(Result::ok) // Filter out Ok values, ignore errors
-        .collect(); // Collect into a Vec<Vec<u8>>
-                    // println!("Obtained keys:");
-    for key in keys.iter() {
-        // println!("Key: len: {} 0x{}", key.0.len(), hex::encode(&key));
-
-        // let account_idx = 48;
-        // let account_key = key.0.as_slice()[account_idx..(account_idx + 32)].to_vec();
-        // let account_id = AccountId32::decode(&mut &account_key[..]);
-        // let account_id = Account::from(account_id.unwrap());
-        // let account_id = String::from(&account_id);
-        // println!("account_id: {}", account_id);
-
-        let class_idx = 96;
-        let class_key = key.as_slice()[class_idx..(class_idx + 8)].to_vec();
-        let class_id = u64::decode(&mut &class_key[..]);
-        // println!("class_id: {:?}", class_id);
-
-        let asset_idx = 120;
-        let asset_key = key.as_slice()[asset_idx..(asset_idx + 8)].to_vec();
-        let asset_id = u64::decode(&mut &asset_key[..]);
-        // println!("asset_id: {:?}", asset_id);
-
-        let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
-
-        if let Some(storage_data) = storage
-            .fetch_raw(key.clone())
-            .await
-            .map_err(map_subxt_err)?
-        {
-            let value = u128::decode(&mut &storage_data[..]);
-            // println!(
-            //     "Class_Id: {:?} AssetId: {:?}  Value: {:?}",
-            //     class_id, asset_id, value
-            // );
-            let item = AssetBalanceItemOutput {
-                class_id: ClassId::from(class_id.unwrap()),
-                asset_id: AssetId::from(asset_id.unwrap()),
-                amount: Balance::from(value.unwrap()),
-            };
-            result_array.push(item);
+fn parse_holder_cursor(cursor: &str) -> error::Result<(subxt::utils::AccountId32, u128)> {
+    cursor
+        .split_once(':')
+        .and_then(|(amount, account)| {
+            let amount = amount.parse().ok()?;
+            let account = subxt::utils::AccountId32::from_str(account).ok()?;
+            Some((account, amount))
+        })
+        .ok_or_else(|| {
+            map_validation_err(vec![FieldError {
+                field: "cursor".into(),
+                reason: "not a cursor of this listing".into(),
+            }])
+        })
+}
+
+/// Accounts holding an asset, from the holder index, a page at a time
+pub async fn holders(
+    data: web::Data<AppState>,
+    req: web::Json<HoldersInput>,
+) -> error::Result<HttpResponse> {
+    let order = req.order.unwrap_or_default();
+    let limit = page_limit(req.limit) as usize;
+    let cursor = req.cursor.as_deref().map(parse_holder_cursor).transpose()?;
+
+    let (mut holders, index) = data
+        .holders
+        .holders(req.class_id.into(), req.asset_id.into())?;
+    let compare = |a: &(subxt::utils::AccountId32, u128), b: &(subxt::utils::AccountId32, u128)| {
+        let amounts = match order {
+            HolderOrder::AmountDesc => b.1.cmp(&a.1),
+            HolderOrder::AmountAsc => a.1.cmp(&b.1),
+        };
+        amounts.then_with(|| a.0.cmp(&b.0))
+    };
+    holders.sort_by(compare);
+
+    let total = holders.len();
+    let start = match &cursor {
+        Some(cursor) => holders.partition_point(|holder| compare(holder, cursor).is_le()),
+        None => 0,
+    };
+    let page = &holders[start..total.min(start + limit)];
+    let next_cursor = match page.last() {
+        Some((account, amount)) if start + page.len() < total => {
+            Some(holder_cursor(account, *amount))
         }
-    }
+        _ => None,
+    };
 
-    Ok(HttpResponse::Ok().json(AssetBalancesOutput {
-        balances: result_array,
+    Ok(HttpResponse::Ok().json(HoldersOutput {
+        holders: page
+            .iter()
+            .map(|(account, amount)| HolderOutput {
+                account: account.clone().into(),
+                amount: (*amount).into(),
+            })
+            .collect(),
+        total,
+        next_cursor,
+        index,
+    }))
+}
+
+/// Total amount of an asset held by all accounts, from the holder index
+pub async fn supply(
+    data: web::Data<AppState>,
+    req: web::Json<SupplyInput>,
+) -> error::Result<HttpResponse> {
+    let (supply, holders, index) = data
+        .holders
+        .supply(req.class_id.into(), req.asset_id.into())?;
+    Ok(HttpResponse::Ok().json(SupplyOutput {
+        class_id: req.class_id,
+        asset_id: req.asset_id,
+        supply: supply.into(),
+        holders,
+        index,
     }))
 }
 
@@ -419,43 +916,121 @@ pub async fn transfer_from(
     data: web::Data<AppState>,
     req: web::Json<TransferFromInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let account_from = subxt::utils::AccountId32::try_from(&req.from).map_err(map_account_err)?;
-    let account_to = subxt::utils::AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let api = &data.api;
-
-    let call = sugarfunge::tx().asset().transfer_from(
-        account_from,
-        account_to,
-        req.class_id.into(),
-        req.asset_id.into(),
-        req.amount.into(),
-    );
+    execute(&data, req.into_inner()).await
+}
 
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::asset::events::Transferred>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(TransferFromOutput {
-            from: event.from.into(),
-            to: event.to.into(),
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-            amount: event.amount.into(),
-            who: event.who.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::asset::events::Transferred"),
-            description: String::new(),
-        })),
+impl Operation for TransferFromInput {
+    type Output = TransferFromOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let account_from =
+            subxt::utils::AccountId32::try_from(&self.from).map_err(map_account_err)?;
+        let account_to = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().asset().transfer_from(
+            account_from,
+            account_to,
+            self.class_id.into(),
+            self.asset_id.into(),
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<TransferFromOutput> {
+        let result = events
+            .find_first::<sugarfunge::asset::events::Transferred>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(TransferFromOutput {
+                from: event.from.into(),
+                to: event.to.into(),
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+                amount: event.amount.into(),
+                who: event.who.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::Transferred"),
+                description: String::new(),
+            })),
+        }
+    }
+}
+
+/// Transfer amounts of several assets of a class from to accounts
+pub async fn batch_transfer_from(
+    data: web::Data<AppState>,
+    req: web::Json<BatchTransferFromInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for BatchTransferFromInput {
+    type Output = BatchTransferFromOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let account_from =
+            subxt::utils::AccountId32::try_from(&self.from).map_err(map_account_err)?;
+        let account_to = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().asset().batch_transfer_from(
+            account_from,
+            account_to,
+            self.class_id.into(),
+            transform_vec_assetid_to_u64(self.asset_ids.clone()),
+            transform_vec_balance_to_u128(&self.amounts),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<BatchTransferFromOutput> {
+        let events = events
+            .find::<sugarfunge::asset::events::BatchTransferred>()
+            .map(|event| {
+                event.map(|event| BatchTransferFromItemOutput {
+                    from: event.from.into(),
+                    to: event.to.into(),
+                    class_id: event.class_id.into(),
+                    asset_ids: event.asset_ids.into_iter().map(AssetId::from).collect(),
+                    amounts: event.amounts.into_iter().map(Balance::from).collect(),
+                    who: event.who.into(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(map_subxt_err)?;
+        if events.is_empty() {
+            return Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::asset::events::BatchTransferred"),
+                description: String::new(),
+            }));
+        }
+        Ok(BatchTransferFromOutput { events })
     }
 }
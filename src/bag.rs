@@ -1,11 +1,11 @@
-use std::str::FromStr;
-
 use crate::state::*;
+use crate::tx::*;
 use crate::util::*;
 use actix_web::{error, web, HttpResponse};
 use serde_json::json;
-use subxt::tx::PairSigner;
+use subxt::blocks::ExtrinsicEvents;
 use subxt::utils::AccountId32;
+use subxt::PolkadotConfig;
 use sugarfunge_api_types::bag::*;
 use sugarfunge_api_types::primitives::*;
 use sugarfunge_api_types::sugarfunge;
@@ -15,43 +15,56 @@ pub async fn register(
     data: web::Data<AppState>,
     req: web::Json<RegisterInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let metadata: Vec<u8> = serde_json::to_vec(&req.metadata).unwrap_or_default();
-    let metadata = BoundedVec(metadata);
-    let api = &data.api;
-
-    let call = sugarfunge::tx()
-        .bag()
-        .register(req.class_id.into(), metadata);
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bag::events::Register>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(RegisterOutput {
-            who: event.who.into(),
-            class_id: event.class_id.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bag::events::Register"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for RegisterInput {
+    type Output = RegisterOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let metadata: Vec<u8> = metadata_bytes(&self.metadata)?;
+        let metadata = BoundedVec(metadata);
+
+        let call = sugarfunge::tx()
+            .bag()
+            .register(self.class_id.into(), metadata);
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<RegisterOutput> {
+        let result = events
+            .find_first::<sugarfunge::bag::events::Register>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(RegisterOutput {
+                who: event.who.into(),
+                class_id: event.class_id.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bag::events::Register"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
-pub fn transform_owners_input(in_owners: Vec<String>) -> Vec<AccountId32> {
+pub fn transform_owners_input(in_owners: &[Account]) -> error::Result<Vec<AccountId32>> {
     in_owners
-        .into_iter()
-        .map(|current_owner| AccountId32::from_str(&current_owner).unwrap())
+        .iter()
+        .map(|current_owner| AccountId32::try_from(current_owner).map_err(map_account_err))
         .collect()
 }
 
@@ -66,39 +79,52 @@ pub async fn create(
     data: web::Data<AppState>,
     req: web::Json<CreateInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let owners = transform_owners_input(transform_vec_account_to_string(req.owners.clone()));
-    let api = &data.api;
-
-    let call = sugarfunge::tx().bag().create(
-        req.class_id.into(),
-        owners,
-        transform_vec_balance_to_u128(&req.shares),
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bag::events::Created>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(CreateOutput {
-            bag: event.bag.into(),
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-            owners: transform_vec_string_to_account(transform_owners_output(event.owners)),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bag::events::AccountCreated"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for CreateInput {
+    type Output = CreateOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let owners = transform_owners_input(&self.owners)?;
+
+        let call = sugarfunge::tx().bag().create(
+            self.class_id.into(),
+            owners,
+            transform_vec_balance_to_u128(&self.shares),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CreateOutput> {
+        let result = events
+            .find_first::<sugarfunge::bag::events::Created>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(CreateOutput {
+                bag: event.bag.into(),
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+                owners: transform_vec_string_to_account(transform_owners_output(event.owners)),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bag::events::AccountCreated"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -106,35 +132,48 @@ pub async fn sweep(
     data: web::Data<AppState>,
     req: web::Json<SweepInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let bag = AccountId32::try_from(&req.bag).map_err(map_account_err)?;
-    let to = AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let api = &data.api;
-
-    let call = sugarfunge::tx().bag().sweep(to.into(), bag.into());
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bag::events::Sweep>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(SweepOutput {
-            bag: event.bag.into(),
-            who: event.who.into(),
-            to: event.to.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bag::events::Sweep"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for SweepInput {
+    type Output = SweepOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let bag = AccountId32::try_from(&self.bag).map_err(map_account_err)?;
+        let to = AccountId32::try_from(&self.to).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().bag().sweep(to.into(), bag.into());
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<SweepOutput> {
+        let result = events
+            .find_first::<sugarfunge::bag::events::Sweep>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(SweepOutput {
+                bag: event.bag.into(),
+                who: event.who.into(),
+                to: event.to.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bag::events::Sweep"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -142,37 +181,50 @@ pub async fn deposit(
     data: web::Data<AppState>,
     req: web::Json<DepositInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let bag = subxt::utils::AccountId32::try_from(&req.bag).map_err(map_account_err)?;
-    let api = &data.api;
-
-    let call = sugarfunge::tx().bag().deposit(
-        bag,
-        transform_vec_classid_to_u64(req.class_ids.clone()),
-        transform_doublevec_assetid_to_u64(req.asset_ids.clone()),
-        transform_doublevec_balance_to_u128(req.amounts.clone()),
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bag::events::Deposit>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(DepositOutput {
-            bag: event.bag.into(),
-            who: event.who.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bag::events::Deposit"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for DepositInput {
+    type Output = DepositOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let bag = subxt::utils::AccountId32::try_from(&self.bag).map_err(map_account_err)?;
+
+        let call = sugarfunge::tx().bag().deposit(
+            bag,
+            transform_vec_classid_to_u64(self.class_ids.clone()),
+            transform_doublevec_assetid_to_u64(self.asset_ids.clone()),
+            transform_doublevec_balance_to_u128(self.amounts.clone()),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<DepositOutput> {
+        let result = events
+            .find_first::<sugarfunge::bag::events::Deposit>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(DepositOutput {
+                bag: event.bag.into(),
+                who: event.who.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bag::events::Deposit"),
+                description: String::new(),
+            })),
+        }
     }
 }
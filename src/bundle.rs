@@ -1,19 +1,19 @@
 use crate::state::*;
+use crate::storage::KeyDecoder;
+use crate::tx::*;
 use crate::util::*;
 use actix_web::{error, web, HttpResponse};
-use codec::Decode;
 use codec::Encode;
 use futures::stream::StreamExt;
 use hex::ToHex;
 use serde_json::json;
 use std::str::FromStr;
-use subxt::tx::PairSigner;
-use subxt::utils::AccountId32;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::PolkadotConfig;
 use sugarfunge_api_types::bundle::*;
 use sugarfunge_api_types::primitives::*;
 use sugarfunge_api_types::sugarfunge;
 use sugarfunge_api_types::sugarfunge::runtime_types::bounded_collections::bounded_vec::BoundedVec;
-use sugarfunge_api_types::sugarfunge::runtime_types::sugarfunge_bundle::Bundle as BundleRuntime;
 
 fn hash(s: &[u8]) -> sp_core::H256 {
     sp_io::hashing::blake2_256(s).into()
@@ -23,60 +23,73 @@ pub async fn register_bundle(
     data: web::Data<AppState>,
     req: web::Json<RegisterBundleInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let schema = (
-        BoundedVec(transform_vec_classid_to_u64(req.schema.class_ids.to_vec())),
-        BoundedVec(
-            req.schema
-                .asset_ids
-                .iter()
-                .map(|x| BoundedVec(transform_vec_assetid_to_u64(x.to_vec())))
-                .collect(),
-        ),
-        BoundedVec(
-            req.schema
-                .amounts
-                .iter()
-                .map(|x| BoundedVec(transform_vec_balance_to_u128(&x.to_vec())))
-                .collect(),
-        ),
-    );
-    let bundle_id = hash(&schema.encode());
-    let metadata: Vec<u8> = serde_json::to_vec(&req.metadata).unwrap_or_default();
-    let metadata = BoundedVec(metadata);
-    let api = &data.api;
-
-    let call = sugarfunge::tx().bundle().register_bundle(
-        req.class_id.into(),
-        req.asset_id.into(),
-        bundle_id,
-        schema,
-        metadata,
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bundle::events::Register>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(RegisterBundleOutput {
-            who: event.who.into(),
-            bundle_id: event.bundle_id.encode_hex(),
-            class_id: event.class_id.into(),
-            asset_id: event.asset_id.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bundle::events::Register"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for RegisterBundleInput {
+    type Output = RegisterBundleOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let schema = (
+            BoundedVec(transform_vec_classid_to_u64(self.schema.class_ids.to_vec())),
+            BoundedVec(
+                self.schema
+                    .asset_ids
+                    .iter()
+                    .map(|x| BoundedVec(transform_vec_assetid_to_u64(x.to_vec())))
+                    .collect(),
+            ),
+            BoundedVec(
+                self.schema
+                    .amounts
+                    .iter()
+                    .map(|x| BoundedVec(transform_vec_balance_to_u128(&x.to_vec())))
+                    .collect(),
+            ),
+        );
+        let bundle_id = hash(&schema.encode());
+        let metadata: Vec<u8> = metadata_bytes(&self.metadata)?;
+        let metadata = BoundedVec(metadata);
+
+        let call = sugarfunge::tx().bundle().register_bundle(
+            self.class_id.into(),
+            self.asset_id.into(),
+            bundle_id,
+            schema,
+            metadata,
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<RegisterBundleOutput> {
+        let result = events
+            .find_first::<sugarfunge::bundle::events::Register>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(RegisterBundleOutput {
+                who: event.who.into(),
+                bundle_id: event.bundle_id.encode_hex(),
+                class_id: event.class_id.into(),
+                asset_id: event.asset_id.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bundle::events::Register"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -84,43 +97,57 @@ pub async fn mint_bundle(
     data: web::Data<AppState>,
     req: web::Json<MintBundleInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let account_from = subxt::utils::AccountId32::try_from(&req.from).map_err(map_account_err)?;
-    let account_to = subxt::utils::AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let bundle_id = sp_core::H256::from_str(req.bundle_id.as_str()).unwrap_or_default();
-    let api = &data.api;
-
-    let call = sugarfunge::tx().bundle().mint_bundle(
-        account_from,
-        account_to,
-        bundle_id,
-        req.amount.into(),
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bundle::events::Mint>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(MintBundleOutput {
-            who: event.who.into(),
-            from: event.from.into(),
-            to: event.to.into(),
-            bundle_id: event.bundle_id.encode_hex(),
-            amount: event.amount.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bundle::events::Mint"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for MintBundleInput {
+    type Output = MintBundleOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let account_from =
+            subxt::utils::AccountId32::try_from(&self.from).map_err(map_account_err)?;
+        let account_to = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+        let bundle_id = sp_core::H256::from_str(self.bundle_id.as_str()).unwrap_or_default();
+
+        let call = sugarfunge::tx().bundle().mint_bundle(
+            account_from,
+            account_to,
+            bundle_id,
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<MintBundleOutput> {
+        let result = events
+            .find_first::<sugarfunge::bundle::events::Mint>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(MintBundleOutput {
+                who: event.who.into(),
+                from: event.from.into(),
+                to: event.to.into(),
+                bundle_id: event.bundle_id.encode_hex(),
+                amount: event.amount.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bundle::events::Mint"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -128,165 +155,134 @@ pub async fn burn_bundle(
     data: web::Data<AppState>,
     req: web::Json<BurnBundleInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let account_from = subxt::utils::AccountId32::try_from(&req.from).map_err(map_account_err)?;
-    let account_to = subxt::utils::AccountId32::try_from(&req.to).map_err(map_account_err)?;
-    let bundle_id = sp_core::H256::from_str(req.bundle_id.as_str()).unwrap_or_default();
-    let api = &data.api;
-
-    let call = sugarfunge::tx().bundle().burn_bundle(
-        account_from,
-        account_to,
-        bundle_id,
-        req.amount.into(),
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::bundle::events::Burn>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(BurnBundleOutput {
-            who: event.who.into(),
-            from: event.from.into(),
-            to: event.to.into(),
-            bundle_id: event.bundle_id.encode_hex(),
-            amount: event.amount.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::bundle::events::Burn"),
-            description: String::new(),
-        })),
-    }
+    execute(&data, req.into_inner()).await
 }
 
-pub async fn get_bundles_id(data: web::Data<AppState>) -> error::Result<HttpResponse> {
-    let api = &data.api;
+impl Operation for BurnBundleInput {
+    type Output = BurnBundleOutput;
 
-    let mut result_array = Vec::new();
-    let query_key = sugarfunge::storage()
-        .bundle()
-        .asset_bundles_iter()
-        .to_root_bytes();
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let account_from =
+            subxt::utils::AccountId32::try_from(&self.from).map_err(map_account_err)?;
+        let account_to = subxt::utils::AccountId32::try_from(&self.to).map_err(map_account_err)?;
+        let bundle_id = sp_core::H256::from_str(self.bundle_id.as_str()).unwrap_or_default();
 
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
+        let call = sugarfunge::tx().bundle().burn_bundle(
+            account_from,
+            account_to,
+            bundle_id,
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
 
-    let keys_stream = storage
-        .fetch_raw_keys(query_key)
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<BurnBundleOutput> {
+        let result = events
+            .find_first::<sugarfunge::bundle::events::Burn>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(BurnBundleOutput {
+                who: event.who.into(),
+                from: event.from.into(),
+                to: event.to.into(),
+                bundle_id: event.bundle_id.encode_hex(),
+                amount: event.amount.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::bundle::events::Burn"),
+                description: String::new(),
+            })),
+        }
+    }
+}
+
+pub async fn get_bundles_id(
+    data: web::Data<AppState>,
+    req: Option<web::Json<GetBundlesInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let decoder = KeyDecoder::new(&data.api.metadata(), "Bundle", "AssetBundles")?;
+
+    // Keys and values are read together, a page at a time, from the same block
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let mut pairs = block
+        .storage()
+        .iter(sugarfunge::storage().bundle().asset_bundles_iter())
         .await
         .map_err(map_subxt_err)?;
-    let keys: Vec<Vec<u8>> = keys_stream
-        .collect::<Vec<_>>() // Collect into a Vec<Result<Vec<u8>, Error>>
-        .await // Await the collection process
-        .into_iter() // Convert into an iterator
-        .filter_map(Result::ok) // Filter out Ok values, ignore errors
-        .collect(); // Collect int
-
-    for key in keys.iter() {
-        // println!("Key: len: {} 0x{}", key.0.len(), hex::encode(&key));
-
-        let class_idx = 48;
-        let class_key = key.as_slice()[class_idx..(class_idx + 8)].to_vec();
-        let class_id = u64::decode(&mut &class_key[..]).unwrap();
-        // println!("class_id: {}", class_id);
-
-        let asset_idx = 72;
-        let asset_key = key.as_slice()[asset_idx..(asset_idx + 8)].to_vec();
-        let asset_id = u64::decode(&mut &asset_key[..]).unwrap();
-        // println!("asset_id: {}", asset_id);
-
-        if let Some(storage_data) = storage
-            .fetch_raw(key.clone())
-            .await
-            .map_err(map_subxt_err)?
-        {
-            let value = sp_core::H256::decode(&mut &storage_data[..]).unwrap();
-            let bundle_id = value.encode_hex();
-
-            let item = BundleItem {
-                class_id: class_id.into(),
-                asset_id: asset_id.into(),
-                bundle_id,
-            };
-            result_array.push(item);
-        }
+
+    let mut result_array = Vec::new();
+    while let Some(pair) = pairs.next().await {
+        let pair = pair.map_err(map_subxt_err)?;
+        let mut keys = decoder.keys(&pair.key_bytes)?;
+        let class_id: u64 = keys.next()?;
+        let asset_id: u64 = keys.next()?;
+        result_array.push(BundleItem {
+            class_id: class_id.into(),
+            asset_id: asset_id.into(),
+            bundle_id: pair.value.encode_hex(),
+        });
     }
 
     Ok(HttpResponse::Ok().json(GetBundles {
         bundles: result_array,
+        block: at_block(&block),
     }))
 }
 
-pub async fn get_bundles_data(data: web::Data<AppState>) -> error::Result<HttpResponse> {
-    let api = &data.api;
-
-    let mut result_array = Vec::new();
-    let query_key = sugarfunge::storage()
-        .bundle()
-        .bundles_iter()
-        .to_root_bytes();
-
-    let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
+pub async fn get_bundles_data(
+    data: web::Data<AppState>,
+    req: Option<web::Json<GetBundlesInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let decoder = KeyDecoder::new(&data.api.metadata(), "Bundle", "Bundles")?;
 
-    let keys_stream = storage
-        .fetch_raw_keys(query_key)
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let mut pairs = block
+        .storage()
+        .iter(sugarfunge::storage().bundle().bundles_iter())
         .await
         .map_err(map_subxt_err)?;
 
-    let keys: Vec<Vec<u8>> = keys_stream
-        .collect::<Vec<_>>() // Collect into a Vec<Result<Vec<u8>, Error>>
-        .await // Await the collection process
-        .into_iter() // Convert into an iterator
-        .filter_map(Result::ok) // Filter out Ok values, ignore errors
-        .collect(); // Collect int
-
-    for key in keys.iter() {
-        // println!("Key: len: {} 0x{}", key.0.len(), hex::encode(&key));
-
-        let bundle_idx = 48;
-        let bundle_key = key.as_slice()[bundle_idx..].to_vec();
-        let bundle_id = sp_core::H256::decode(&mut &bundle_key[..]).unwrap();
-        let bundle_id_value: BundleId = bundle_id.encode_hex();
-
-        if let Some(storage_data) = storage
-            .fetch_raw(key.clone())
-            .await
-            .map_err(map_subxt_err)?
-        {
-            let value = BundleRuntime::<
-                u64,
-                u64,
-                (Vec<u64>, Vec<Vec<u64>>, Vec<Vec<u128>>),
-                AccountId32,
-                Vec<u8>,
-            >::decode(&mut &storage_data[..])
-            .unwrap();
-
-            let item = BundleDataItem {
-                bundle_id: bundle_id_value,
-                creator: value.creator.into(),
-                class_id: value.class_id.into(),
-                asset_id: value.asset_id.into(),
-                metadata: serde_json::from_slice(value.metadata.as_slice()).unwrap_or_default(),
-                schema: BundleSchema {
-                    class_ids: get_schema_class_ids(value.schema.0),
-                    asset_ids: get_schema_vec_asset_ids(value.schema.1),
-                    amounts: get_schema_vec_amounts(value.schema.2),
-                },
-            };
-            result_array.push(item);
-        }
+    let mut result_array = Vec::new();
+    while let Some(pair) = pairs.next().await {
+        let pair = pair.map_err(map_subxt_err)?;
+        let bundle_id: sp_core::H256 = decoder.keys(&pair.key_bytes)?.next()?;
+        let value = pair.value;
+        let (class_ids, asset_ids, amounts) = value.schema;
+        result_array.push(BundleDataItem {
+            bundle_id: bundle_id.encode_hex(),
+            creator: value.creator.into(),
+            class_id: value.class_id.into(),
+            asset_id: value.asset_id.into(),
+            metadata: serde_json::from_slice(value.metadata.0.as_slice()).unwrap_or_default(),
+            schema: BundleSchema {
+                class_ids: get_schema_class_ids(class_ids.0),
+                asset_ids: get_schema_vec_asset_ids(
+                    asset_ids.0.into_iter().map(|ids| ids.0).collect(),
+                ),
+                amounts: get_schema_vec_amounts(
+                    amounts.0.into_iter().map(|amounts| amounts.0).collect(),
+                ),
+            },
+        });
     }
     Ok(HttpResponse::Ok().json(GetBundlesData {
         bundles: result_array,
+        block: at_block(&block),
     }))
 }
 
@@ -0,0 +1,86 @@
+use crate::dynamic::*;
+use crate::state::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use std::collections::BTreeMap;
+use sugarfunge_api_types::chain::*;
+use sugarfunge_api_types::sugarfunge;
+
+/// Constants of all pallets, or of the given one, decoded to JSON
+pub async fn constants(
+    data: web::Data<AppState>,
+    req: Option<web::Json<ChainConstantsInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let api = &data.api;
+    let metadata = api.metadata();
+
+    let pallets: Vec<_> = metadata
+        .pallets()
+        .filter(|pallet| match &req.pallet {
+            Some(name) => pallet.name() == name,
+            None => true,
+        })
+        .collect();
+    if let (Some(name), true) = (&req.pallet, pallets.is_empty()) {
+        return Err(map_dynamic_err(format!("pallet: unknown pallet {}", name)));
+    }
+
+    let mut constants = BTreeMap::new();
+    for pallet in pallets {
+        let mut values = BTreeMap::new();
+        for constant in pallet.constants() {
+            let address = subxt::dynamic::constant(pallet.name(), constant.name());
+            let value = api
+                .constants()
+                .at(&address)
+                .map_err(map_subxt_err)?
+                .to_value()
+                .map_err(map_subxt_err)?;
+            values.insert(
+                constant.name().to_string(),
+                value_to_json(&value, metadata.types()),
+            );
+        }
+        constants.insert(pallet.name().to_string(), values);
+    }
+    Ok(HttpResponse::Ok().json(ChainConstantsOutput { constants }))
+}
+
+/// Name, token, address format and runtime version of the chain
+pub async fn info(data: web::Data<AppState>) -> error::Result<HttpResponse> {
+    let api = &data.api;
+    let chain = data.rpc.system_chain().await.map_err(map_subxt_err)?;
+    let properties = data.rpc.system_properties().await.map_err(map_subxt_err)?;
+    let runtime_version = data
+        .rpc
+        .state_get_runtime_version(None)
+        .await
+        .map_err(map_subxt_err)?;
+    let ss58_prefix = api
+        .constants()
+        .at(&sugarfunge::constants().system().ss58_prefix())
+        .map_err(map_subxt_err)?;
+
+    // Chains with several tokens list them all, the first is the native one
+    let first = |value: Option<&serde_json::Value>| match value {
+        Some(serde_json::Value::Array(values)) => values.first().cloned(),
+        value => value.cloned(),
+    };
+    Ok(HttpResponse::Ok().json(ChainInfoOutput {
+        chain,
+        token_symbol: first(properties.get("tokenSymbol"))
+            .and_then(|symbol| symbol.as_str().map(String::from)),
+        token_decimals: first(properties.get("tokenDecimals"))
+            .and_then(|decimals| decimals.as_u64())
+            .map(|decimals| decimals as u32),
+        ss58_prefix,
+        genesis_hash: format!("{:?}", api.genesis_hash()),
+        spec_name: runtime_version
+            .other
+            .get("specName")
+            .and_then(|name| name.as_str().map(String::from)),
+        spec_version: runtime_version.spec_version,
+        transaction_version: runtime_version.transaction_version,
+    }))
+}
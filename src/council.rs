@@ -0,0 +1,496 @@
+use crate::dynamic::call_name;
+use crate::state::*;
+use crate::sudo::check_sudid;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use codec::Encode;
+use serde_json::json;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::PolkadotConfig;
+use sugarfunge_api_types::council::*;
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::sugarfunge;
+use sugarfunge_api_types::sugarfunge::runtime_types::pallet_collective::pallet::Call as CouncilCall;
+use sugarfunge_api_types::sugarfunge::runtime_types::pallet_transaction_payment::types::RuntimeDispatchInfo;
+use sugarfunge_api_types::sugarfunge::runtime_types::sp_runtime::DispatchError as RuntimeDispatchError;
+use sugarfunge_api_types::sugarfunge::runtime_types::sp_weights::weight_v2::Weight as RuntimeWeight;
+use sugarfunge_api_types::sugarfunge::runtime_types::sugarfunge_runtime::RuntimeCall;
+use sugarfunge_api_types::validation::FieldError;
+
+fn council_call_data(call: CouncilCall) -> error::Result<Vec<u8>> {
+    Ok(RuntimeCall::Council(call).encode())
+}
+
+/// Council calls restricted to root go through sudo
+fn sudo_council_call_data(api: &ClientAPI, call: CouncilCall) -> error::Result<Vec<u8>> {
+    let call = sugarfunge::tx().sudo().sudo(RuntimeCall::Council(call));
+    api.tx().call_data(&call).map_err(map_subxt_err)
+}
+
+fn dispatch_result(result: &Result<(), RuntimeDispatchError>) -> Option<serde_json::Value> {
+    result.as_ref().err().map(runtime_dispatch_error_value)
+}
+
+/// Propose an operation as a council motion
+pub async fn propose(
+    data: web::Data<AppState>,
+    req: web::Json<ProposeInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for ProposeInput {
+    type Output = ProposeOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        // Motions are dispatched with the council origin, not through sudo
+        let proposal = without_sudo(operation_call(
+            api,
+            &self.module,
+            &self.operation,
+            &self.input,
+        )?);
+        let length_bound = proposal.encoded_size() as u32;
+        let call = CouncilCall::propose {
+            threshold: self.threshold,
+            proposal: Box::new(proposal),
+            length_bound,
+        };
+        council_call_data(call)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<ProposeOutput> {
+        let proposed = events
+            .find_first::<sugarfunge::council::events::Proposed>()
+            .map_err(map_subxt_err)?;
+        if let Some(event) = proposed {
+            return Ok(ProposeOutput {
+                proposal_hash: format!("{:?}", event.proposal_hash),
+                proposal_index: Some(event.proposal_index),
+                threshold: event.threshold,
+                executed: false,
+                error: None,
+            });
+        }
+        // Below a threshold of 2 the proposal is executed at once
+        let executed = events
+            .find_first::<sugarfunge::council::events::Executed>()
+            .map_err(map_subxt_err)?;
+        match executed {
+            Some(event) => Ok(ProposeOutput {
+                proposal_hash: format!("{:?}", event.proposal_hash),
+                proposal_index: None,
+                threshold: self.threshold,
+                executed: true,
+                error: dispatch_result(&event.result),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::council::events::Proposed"),
+                description: "Error in council::propose".into(),
+            })),
+        }
+    }
+}
+
+/// Vote on an open motion as a council member
+pub async fn vote(
+    data: web::Data<AppState>,
+    req: web::Json<VoteInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for VoteInput {
+    type Output = VoteOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, _api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call = CouncilCall::vote {
+            proposal: parse_hash(&self.proposal_hash)?,
+            index: self.proposal_index,
+            approve: self.approve,
+        };
+        council_call_data(call)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<VoteOutput> {
+        let result = events
+            .find_first::<sugarfunge::council::events::Voted>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(VoteOutput {
+                account: event.account.into(),
+                proposal_hash: format!("{:?}", event.proposal_hash),
+                approve: event.voted,
+                ayes: event.yes,
+                nays: event.no,
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::council::events::Voted"),
+                description: "Error in council::vote".into(),
+            })),
+        }
+    }
+}
+
+/// Close a motion once it has enough votes or its voting period ended,
+/// the length and weight of the proposal are looked up if not given
+pub async fn close(
+    data: web::Data<AppState>,
+    req: web::Json<CloseInput>,
+) -> error::Result<HttpResponse> {
+    let mut req = req.into_inner();
+    if req.length_bound.is_none() || req.weight_bound.is_none() {
+        let api = &data.api;
+        let proposal_hash = parse_hash(&req.proposal_hash)?;
+        let storage = api.storage().at_latest().await.map_err(map_subxt_err)?;
+        let proposal = storage
+            .fetch(&sugarfunge::storage().council().proposal_of(proposal_hash))
+            .await
+            .map_err(map_subxt_err)?
+            .ok_or_else(|| {
+                map_request_err(RequestError {
+                    message: json!(format!("Unknown proposal {}", req.proposal_hash)),
+                    description: "Error in council::close".into(),
+                })
+            })?;
+        let proposal = proposal.encode();
+
+        let mut call_parameters = proposal.clone();
+        (proposal.len() as u32).encode_to(&mut call_parameters);
+        let runtime_api = api.runtime_api().at_latest().await.map_err(map_subxt_err)?;
+        let info: RuntimeDispatchInfo<u128, RuntimeWeight> = runtime_api
+            .call_raw(
+                "TransactionPaymentCallApi_query_call_info",
+                Some(&call_parameters),
+            )
+            .await
+            .map_err(map_subxt_err)?;
+
+        req.length_bound.get_or_insert(proposal.len() as u32);
+        req.weight_bound.get_or_insert(Weight {
+            ref_time: info.weight.ref_time,
+            proof_size: info.weight.proof_size,
+        });
+    }
+    execute(&data, req).await
+}
+
+impl Operation for CloseInput {
+    type Output = CloseOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, _api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let (Some(length_bound), Some(weight_bound)) = (self.length_bound, self.weight_bound)
+        else {
+            return Err(map_validation_err(vec![FieldError {
+                field: "length_bound".into(),
+                reason: "length_bound and weight_bound are required unless closing through council/close".into(),
+            }]));
+        };
+        let call = CouncilCall::close {
+            proposal_hash: parse_hash(&self.proposal_hash)?,
+            index: self.proposal_index,
+            proposal_weight_bound: RuntimeWeight {
+                ref_time: weight_bound.ref_time,
+                proof_size: weight_bound.proof_size,
+            },
+            length_bound,
+        };
+        council_call_data(call)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CloseOutput> {
+        let closed = events
+            .find_first::<sugarfunge::council::events::Closed>()
+            .map_err(map_subxt_err)?;
+        let approved = events
+            .find_first::<sugarfunge::council::events::Approved>()
+            .map_err(map_subxt_err)?;
+        let executed = events
+            .find_first::<sugarfunge::council::events::Executed>()
+            .map_err(map_subxt_err)?;
+        match closed {
+            Some(event) => Ok(CloseOutput {
+                proposal_hash: format!("{:?}", event.proposal_hash),
+                approved: approved.is_some(),
+                ayes: event.yes,
+                nays: event.no,
+                executed: executed.is_some(),
+                error: executed.and_then(|event| dispatch_result(&event.result)),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::council::events::Closed"),
+                description: "Error in council::close".into(),
+            })),
+        }
+    }
+}
+
+/// Disapprove a motion, through sudo
+pub async fn disapprove(
+    data: web::Data<AppState>,
+    req: web::Json<DisapproveInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for DisapproveInput {
+    type Output = DisapproveOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call = CouncilCall::disapprove_proposal {
+            proposal_hash: parse_hash(&self.proposal_hash)?,
+        };
+        sudo_council_call_data(api, call)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<DisapproveOutput> {
+        check_sudid(events)?;
+        let result = events
+            .find_first::<sugarfunge::council::events::Disapproved>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(DisapproveOutput {
+                proposal_hash: format!("{:?}", event.proposal_hash),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::council::events::Disapproved"),
+                description: "Error in council::disapprove".into(),
+            })),
+        }
+    }
+}
+
+/// Dispatch an operation with the origin of a single council member
+pub async fn member_execute(
+    data: web::Data<AppState>,
+    req: web::Json<ExecuteInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for ExecuteInput {
+    type Output = ExecuteOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let proposal = without_sudo(operation_call(
+            api,
+            &self.module,
+            &self.operation,
+            &self.input,
+        )?);
+        let length_bound = proposal.encoded_size() as u32;
+        let call = CouncilCall::execute {
+            proposal: Box::new(proposal),
+            length_bound,
+        };
+        council_call_data(call)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<ExecuteOutput> {
+        let result = events
+            .find_first::<sugarfunge::council::events::MemberExecuted>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(ExecuteOutput {
+                proposal_hash: format!("{:?}", event.proposal_hash),
+                success: event.result.is_ok(),
+                error: dispatch_result(&event.result),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::council::events::MemberExecuted"),
+                description: "Error in council::execute".into(),
+            })),
+        }
+    }
+}
+
+/// Replace the council members, through sudo, the current number of
+/// members is read from the latest block if not given
+pub async fn set_members(
+    data: web::Data<AppState>,
+    req: web::Json<SetMembersInput>,
+) -> error::Result<HttpResponse> {
+    let mut req = req.into_inner();
+    if req.old_count.is_none() {
+        let storage = data
+            .api
+            .storage()
+            .at_latest()
+            .await
+            .map_err(map_subxt_err)?;
+        let members = storage
+            .fetch_or_default(&sugarfunge::storage().council().members())
+            .await
+            .map_err(map_subxt_err)?;
+        req.old_count = Some(members.len() as u32);
+    }
+    execute(&data, req).await
+}
+
+impl Operation for SetMembersInput {
+    type Output = SetMembersOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let new_members = self
+            .members
+            .iter()
+            .map(subxt::utils::AccountId32::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(map_account_err)?;
+        let prime = self
+            .prime
+            .as_ref()
+            .map(subxt::utils::AccountId32::try_from)
+            .transpose()
+            .map_err(map_account_err)?;
+        let Some(old_count) = self.old_count else {
+            return Err(map_validation_err(vec![FieldError {
+                field: "old_count".into(),
+                reason: "required unless set through council/set_members".into(),
+            }]));
+        };
+        let call = CouncilCall::set_members {
+            new_members,
+            prime,
+            old_count,
+        };
+        sudo_council_call_data(api, call)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<SetMembersOutput> {
+        // Setting the members emits no event of its own
+        check_sudid(events)?;
+        Ok(SetMembersOutput {
+            members: self.members.clone(),
+            prime: self.prime.clone(),
+        })
+    }
+}
+
+/// Council members and open motions with their votes
+pub async fn proposals(
+    data: web::Data<AppState>,
+    req: Option<web::Json<ProposalsInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let metadata = data.api.metadata();
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let storage = block.storage();
+    let council = sugarfunge::storage().council();
+
+    let members = storage
+        .fetch_or_default(&council.members())
+        .await
+        .map_err(map_subxt_err)?;
+    let prime = storage
+        .fetch(&council.prime())
+        .await
+        .map_err(map_subxt_err)?;
+    let hashes = storage
+        .fetch_or_default(&council.proposals())
+        .await
+        .map_err(map_subxt_err)?;
+
+    let mut proposals = Vec::new();
+    for hash in hashes.0 {
+        let Some(votes) = storage
+            .fetch(&council.voting(hash))
+            .await
+            .map_err(map_subxt_err)?
+        else {
+            continue;
+        };
+        let proposal = storage
+            .fetch(&council.proposal_of(hash))
+            .await
+            .map_err(map_subxt_err)?;
+        proposals.push(ProposalOutput {
+            proposal_hash: format!("{:?}", hash),
+            proposal_index: votes.index,
+            threshold: votes.threshold,
+            ayes: votes.ayes.into_iter().map(Account::from).collect(),
+            nays: votes.nays.into_iter().map(Account::from).collect(),
+            end: votes.end,
+            call: proposal.and_then(|proposal| call_name(&metadata, &proposal.encode())),
+        });
+    }
+    Ok(HttpResponse::Ok().json(ProposalsOutput {
+        members: members.into_iter().map(Account::from).collect(),
+        prime: prime.map(Account::from),
+        proposals,
+        block: at_block(&block),
+    }))
+}
@@ -0,0 +1,80 @@
+use crate::fees::query_fee;
+use crate::state::*;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use serde_json::json;
+use subxt::backend::legacy::rpc_methods::DryRunResult;
+use subxt::tx::Signer as _;
+use sugarfunge_api_types::tx::*;
+
+async fn dry_run_operation<O: Operation>(
+    data: &AppState,
+    input: serde_json::Value,
+) -> error::Result<HttpResponse> {
+    let op: O = parse_operation(input)?;
+    let options = op.tx_options().cloned().unwrap_or_default();
+    let signer = get_signer(data, op.signer())?;
+    let call = encode_call(&data.api, &op)?;
+    let api = &data.api;
+    let params = build_params(options_mortality(api, &options).await?, options_tip(&op));
+
+    // Same nonce as the next submission, transactions in the pool included
+    let nonce = match options.nonce {
+        Some(nonce) => nonce,
+        None => data
+            .rpc
+            .system_account_next_index(&signer.account_id())
+            .await
+            .map_err(map_subxt_err)?,
+    };
+    let extrinsic = api
+        .tx()
+        .create_signed_with_nonce(&call, &signer, nonce, params)
+        .map_err(map_subxt_err)?;
+
+    let result = data
+        .rpc
+        .dry_run(extrinsic.encoded(), None)
+        .await
+        .map_err(map_subxt_err)?
+        .into_dry_run_result(&api.metadata())
+        .map_err(map_subxt_err)?;
+    // An invalid extrinsic is never included, so it pays no fee
+    let output = match result {
+        DryRunResult::Success => DryRunOutput {
+            outcome: DryRunOutcome::Success,
+            error: None,
+            fee: Some(query_fee(api, extrinsic.encoded()).await?),
+            events: None,
+        },
+        DryRunResult::DispatchError(e) => DryRunOutput {
+            outcome: DryRunOutcome::DispatchError,
+            error: Some(dispatch_error_value(&e)),
+            fee: Some(query_fee(api, extrinsic.encoded()).await?),
+            events: None,
+        },
+        DryRunResult::TransactionValidityError => DryRunOutput {
+            outcome: DryRunOutcome::Invalid,
+            error: Some(json!("Transaction is invalid")),
+            fee: None,
+            events: None,
+        },
+    };
+    Ok(HttpResponse::Ok().json(output))
+}
+
+/// Apply an operation to the best block without submitting it
+pub async fn dry_run(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    req: web::Json<serde_json::Value>,
+) -> error::Result<HttpResponse> {
+    let (module, operation) = path.into_inner();
+    let input = req.into_inner();
+    with_operation!(
+        module.as_str(),
+        operation.as_str(),
+        dry_run_operation(&data, input)
+    )
+}
@@ -0,0 +1,468 @@
+use crate::state::*;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use scale_info::form::PortableForm;
+use scale_info::{Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde_json::{json, Value as JsonValue};
+use subxt::blocks::ExtrinsicEvents;
+use subxt::ext::scale_value::{Composite, Primitive, Value, ValueDef};
+use subxt::utils::AccountId32;
+use subxt::{Metadata, PolkadotConfig};
+use sugarfunge_api_types::dynamic::*;
+use sugarfunge_api_types::primitives::{parse_account_id, Signer, TxOptions};
+use sugarfunge_api_types::validation::FieldError;
+
+/// Errors are reported as `path: reason`
+pub fn map_dynamic_err(message: String) -> actix_web::Error {
+    let (field, reason) = message.split_once(": ").unwrap_or(("", &message));
+    map_validation_err(vec![FieldError {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    }])
+}
+
+/// Bytes given as hex, as an SS58 address for 32 byte arrays, or as text
+fn json_to_bytes(json: &JsonValue, len: Option<usize>, path: &str) -> Result<Vec<u8>, String> {
+    let JsonValue::String(text) = json else {
+        return Err(format!("{}: expected bytes", path));
+    };
+    let bytes = match text.strip_prefix("0x") {
+        Some(hex) => hex::decode(hex).map_err(|_| format!("{}: invalid hex", path))?,
+        None if len == Some(32) => parse_account_id(text)
+            .map_err(|_| format!("{}: invalid address", path))?
+            .as_ref()
+            .to_vec(),
+        None => text.as_bytes().to_vec(),
+    };
+    match len {
+        Some(len) if bytes.len() != len => Err(format!("{}: expected {} bytes", path, len)),
+        _ => Ok(bytes),
+    }
+}
+
+fn is_u8(type_id: u32, types: &PortableRegistry) -> bool {
+    types.resolve(type_id).map_or(false, |ty| {
+        matches!(ty.type_def, TypeDef::Primitive(TypeDefPrimitive::U8))
+    })
+}
+
+fn json_to_unsigned(json: &JsonValue, max: u128, path: &str) -> Result<Value, String> {
+    let value = match json {
+        JsonValue::Number(number) => number.as_u64().map(u128::from),
+        JsonValue::String(text) => text.parse::<u128>().ok(),
+        _ => None,
+    };
+    match value {
+        Some(value) if value <= max => Ok(Value::u128(value)),
+        _ => Err(format!("{}: expected an integer up to {}", path, max)),
+    }
+}
+
+fn json_to_signed(json: &JsonValue, min: i128, max: i128, path: &str) -> Result<Value, String> {
+    let value = match json {
+        JsonValue::Number(number) => number.as_i64().map(i128::from),
+        JsonValue::String(text) => text.parse::<i128>().ok(),
+        _ => None,
+    };
+    match value {
+        Some(value) if (min..=max).contains(&value) => Ok(Value::i128(value)),
+        _ => Err(format!(
+            "{}: expected an integer between {} and {}",
+            path, min, max
+        )),
+    }
+}
+
+fn json_to_primitive(
+    json: &JsonValue,
+    primitive: &TypeDefPrimitive,
+    path: &str,
+) -> Result<Value, String> {
+    match primitive {
+        TypeDefPrimitive::Bool => json
+            .as_bool()
+            .map(Value::bool)
+            .ok_or_else(|| format!("{}: expected a boolean", path)),
+        TypeDefPrimitive::Char => {
+            let mut chars = json.as_str().unwrap_or_default().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Value::char(c)),
+                _ => Err(format!("{}: expected a single character", path)),
+            }
+        }
+        TypeDefPrimitive::Str => json
+            .as_str()
+            .map(Value::string)
+            .ok_or_else(|| format!("{}: expected a string", path)),
+        TypeDefPrimitive::U8 => json_to_unsigned(json, u8::MAX.into(), path),
+        TypeDefPrimitive::U16 => json_to_unsigned(json, u16::MAX.into(), path),
+        TypeDefPrimitive::U32 => json_to_unsigned(json, u32::MAX.into(), path),
+        TypeDefPrimitive::U64 => json_to_unsigned(json, u64::MAX.into(), path),
+        TypeDefPrimitive::U128 => json_to_unsigned(json, u128::MAX, path),
+        TypeDefPrimitive::I8 => json_to_signed(json, i8::MIN.into(), i8::MAX.into(), path),
+        TypeDefPrimitive::I16 => json_to_signed(json, i16::MIN.into(), i16::MAX.into(), path),
+        TypeDefPrimitive::I32 => json_to_signed(json, i32::MIN.into(), i32::MAX.into(), path),
+        TypeDefPrimitive::I64 => json_to_signed(json, i64::MIN.into(), i64::MAX.into(), path),
+        TypeDefPrimitive::I128 => json_to_signed(json, i128::MIN, i128::MAX, path),
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+            Err(format!("{}: 256 bit integers are not supported", path))
+        }
+    }
+}
+
+fn json_to_items(
+    json: &JsonValue,
+    type_id: u32,
+    len: Option<usize>,
+    types: &PortableRegistry,
+    path: &str,
+) -> Result<Value, String> {
+    if is_u8(type_id, types) && json.is_string() {
+        return Ok(Value::from_bytes(json_to_bytes(json, len, path)?));
+    }
+    let JsonValue::Array(items) = json else {
+        return Err(format!("{}: expected an array", path));
+    };
+    if let Some(len) = len.filter(|len| *len != items.len()) {
+        return Err(format!("{}: expected {} items", path, len));
+    }
+    let items = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| json_to_value(item, type_id, types, &format!("{}[{}]", path, index)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::unnamed_composite(items))
+}
+
+/// Fields given as an object by name, as an array in order, or directly
+/// when there is only one
+pub fn json_to_fields(
+    json: &JsonValue,
+    fields: &[Field<PortableForm>],
+    types: &PortableRegistry,
+    path: &str,
+) -> Result<Composite<()>, String> {
+    let named = fields.iter().all(|field| field.name.is_some()) && !fields.is_empty();
+    match json {
+        JsonValue::Object(object) if named => {
+            if let Some(key) = object
+                .keys()
+                .find(|key| !fields.iter().any(|field| field.name.as_ref() == Some(*key)))
+            {
+                return Err(format!("{}.{}: unknown field", path, key));
+            }
+            let values = fields
+                .iter()
+                .map(|field| {
+                    let name = field.name.clone().unwrap_or_default();
+                    let field_path = format!("{}.{}", path, name);
+                    let value = object.get(&name).unwrap_or(&JsonValue::Null);
+                    Ok((name, json_to_value(value, field.ty.id, types, &field_path)?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(Composite::Named(values))
+        }
+        JsonValue::Array(items) if items.len() == fields.len() && fields.len() != 1 => {
+            let values = fields
+                .iter()
+                .zip(items)
+                .enumerate()
+                .map(|(index, (field, item))| {
+                    let field_path = match &field.name {
+                        Some(name) => format!("{}.{}", path, name),
+                        None => format!("{}[{}]", path, index),
+                    };
+                    json_to_value(item, field.ty.id, types, &field_path)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(match named {
+                true => Composite::Named(
+                    fields
+                        .iter()
+                        .map(|field| field.name.clone().unwrap_or_default())
+                        .zip(values)
+                        .collect(),
+                ),
+                false => Composite::Unnamed(values),
+            })
+        }
+        JsonValue::Null | JsonValue::Array(_) if fields.is_empty() => {
+            Ok(Composite::Unnamed(Vec::new()))
+        }
+        _ if fields.len() == 1 => {
+            let field = &fields[0];
+            let value = json_to_value(json, field.ty.id, types, path)?;
+            Ok(match &field.name {
+                Some(name) => Composite::Named(vec![(name.clone(), value)]),
+                None => Composite::Unnamed(vec![value]),
+            })
+        }
+        _ => Err(format!("{}: expected {} fields", path, fields.len())),
+    }
+}
+
+/// Convert JSON into a value of the given type. Accounts are given as SS58
+/// addresses or hex, byte strings as hex or text, large integers as strings,
+/// enum variants as `"Variant"` or `{"Variant": fields}` and `None` as null.
+pub fn json_to_value(
+    json: &JsonValue,
+    type_id: u32,
+    types: &PortableRegistry,
+    path: &str,
+) -> Result<Value, String> {
+    let ty = types
+        .resolve(type_id)
+        .ok_or_else(|| format!("{}: unknown type {}", path, type_id))?;
+    let name = ty
+        .path
+        .segments
+        .last()
+        .map(String::as_str)
+        .unwrap_or_default();
+    match &ty.type_def {
+        TypeDef::Composite(composite) => Ok(Value {
+            value: ValueDef::Composite(json_to_fields(json, &composite.fields, types, path)?),
+            context: (),
+        }),
+        TypeDef::Variant(variant) => {
+            let find = |name: &str| {
+                variant
+                    .variants
+                    .iter()
+                    .find(|variant| variant.name == name)
+                    .ok_or_else(|| format!("{}: unknown variant {}", path, name))
+            };
+            let (selected, fields) = match json {
+                JsonValue::Null if name == "Option" => (find("None")?, &JsonValue::Null),
+                _ if name == "Option" => (find("Some")?, json),
+                // Addresses are given as the account id
+                JsonValue::String(_) if name == "MultiAddress" => (find("Id")?, json),
+                JsonValue::String(variant) => (find(variant)?, &JsonValue::Null),
+                JsonValue::Object(object) if object.len() == 1 => {
+                    let (variant, fields) = object.iter().next().unwrap();
+                    (find(variant)?, fields)
+                }
+                _ => return Err(format!("{}: expected a variant of {}", path, name)),
+            };
+            let path = format!("{}.{}", path, selected.name);
+            let values = json_to_fields(fields, &selected.fields, types, &path)?;
+            Ok(Value::variant(selected.name.clone(), values))
+        }
+        TypeDef::Sequence(sequence) => {
+            json_to_items(json, sequence.type_param.id, None, types, path)
+        }
+        TypeDef::Array(array) => json_to_items(
+            json,
+            array.type_param.id,
+            Some(array.len as usize),
+            types,
+            path,
+        ),
+        TypeDef::Tuple(tuple) => {
+            let items: Vec<_> = tuple.fields.iter().map(|field| field.id).collect();
+            match (json, items.as_slice()) {
+                (JsonValue::Null, []) => Ok(Value::unnamed_composite(Vec::new())),
+                (_, [item]) if !json.is_array() => {
+                    Ok(Value::unnamed_composite(vec![json_to_value(
+                        json, *item, types, path,
+                    )?]))
+                }
+                (JsonValue::Array(values), _) if values.len() == items.len() => {
+                    let values = items
+                        .iter()
+                        .zip(values)
+                        .enumerate()
+                        .map(|(index, (item, value))| {
+                            json_to_value(value, *item, types, &format!("{}[{}]", path, index))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(Value::unnamed_composite(values))
+                }
+                _ => Err(format!("{}: expected {} items", path, items.len())),
+            }
+        }
+        TypeDef::Primitive(primitive) => json_to_primitive(json, primitive, path),
+        TypeDef::Compact(compact) => json_to_value(json, compact.type_param.id, types, path),
+        TypeDef::BitSequence(_) => Err(format!("{}: bit sequences are not supported", path)),
+    }
+}
+
+/// Pallet and call names of encoded call data, e.g. `Asset.mint`
+pub fn call_name(metadata: &Metadata, call_data: &[u8]) -> Option<String> {
+    let pallet = metadata.pallet_by_index(*call_data.first()?)?;
+    let variant = pallet.call_variant_by_index(*call_data.get(1)?)?;
+    Some(format!("{}.{}", pallet.name(), variant.name))
+}
+
+/// Encode a call of any pallet from its JSON arguments
+pub fn dynamic_call_data(
+    api: &ClientAPI,
+    pallet: &str,
+    call: &str,
+    args: &JsonValue,
+) -> error::Result<Vec<u8>> {
+    let metadata = api.metadata();
+    let variant = metadata
+        .pallet_by_name(pallet)
+        .and_then(|pallet| pallet.call_variant_by_name(call))
+        .ok_or_else(|| map_dynamic_err(format!("call: unknown call {}::{}", pallet, call)))?;
+    let fields =
+        json_to_fields(args, &variant.fields, metadata.types(), "args").map_err(map_dynamic_err)?;
+    let payload = subxt::dynamic::tx(pallet, call, fields);
+    api.tx().call_data(&payload).map_err(map_subxt_err)
+}
+
+fn primitive_to_json(primitive: &Primitive) -> JsonValue {
+    match primitive {
+        Primitive::Bool(value) => json!(value),
+        Primitive::Char(value) => json!(value.to_string()),
+        Primitive::String(value) => json!(value),
+        // Numbers beyond 64 bits are given as strings, like in the inputs
+        Primitive::U128(value) => match u64::try_from(*value) {
+            Ok(value) => json!(value),
+            Err(_) => json!(value.to_string()),
+        },
+        Primitive::I128(value) => match i64::try_from(*value) {
+            Ok(value) => json!(value),
+            Err(_) => json!(value.to_string()),
+        },
+        Primitive::U256(value) | Primitive::I256(value) => {
+            json!(format!("0x{}", hex::encode(value)))
+        }
+    }
+}
+
+/// Bytes of a sequence or array of `u8`
+fn composite_bytes(composite: &Composite<u32>) -> Option<Vec<u8>> {
+    composite
+        .values()
+        .map(|value| match value.value {
+            ValueDef::Primitive(Primitive::U128(byte)) => u8::try_from(byte).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fields as an object by name, or an array in order, a single
+/// unnamed field is given directly
+pub fn fields_to_json(composite: &Composite<u32>, types: &PortableRegistry) -> JsonValue {
+    match composite {
+        Composite::Named(fields) => JsonValue::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(value, types)))
+                .collect(),
+        ),
+        Composite::Unnamed(values) if values.len() == 1 => value_to_json(&values[0], types),
+        Composite::Unnamed(values) => JsonValue::Array(
+            values
+                .iter()
+                .map(|value| value_to_json(value, types))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert a decoded value into JSON, in the same forms as accepted
+/// by [`json_to_value`]
+pub fn value_to_json(value: &Value<u32>, types: &PortableRegistry) -> JsonValue {
+    let ty = types.resolve(value.context);
+    let name = ty
+        .and_then(|ty| ty.path.segments.last())
+        .map(String::as_str)
+        .unwrap_or_default();
+    match &value.value {
+        ValueDef::Composite(composite) => {
+            let bytes = match ty.map(|ty| &ty.type_def) {
+                Some(TypeDef::Sequence(sequence)) if is_u8(sequence.type_param.id, types) => {
+                    composite_bytes(composite)
+                }
+                Some(TypeDef::Array(array)) if is_u8(array.type_param.id, types) => {
+                    composite_bytes(composite)
+                }
+                _ => None,
+            };
+            match bytes {
+                Some(bytes) => json!(format!("0x{}", hex::encode(bytes))),
+                None if name == "AccountId32" => match composite.values().next() {
+                    Some(Value {
+                        value: ValueDef::Composite(inner),
+                        ..
+                    }) => match composite_bytes(inner).map(<[u8; 32]>::try_from) {
+                        Some(Ok(bytes)) => json!(AccountId32(bytes).to_string()),
+                        _ => fields_to_json(composite, types),
+                    },
+                    _ => fields_to_json(composite, types),
+                },
+                None => fields_to_json(composite, types),
+            }
+        }
+        ValueDef::Variant(variant) if name == "Option" => match variant.name.as_str() {
+            "None" => JsonValue::Null,
+            _ => fields_to_json(&variant.values, types),
+        },
+        ValueDef::Variant(variant) if variant.values.is_empty() => json!(variant.name),
+        ValueDef::Variant(variant) => JsonValue::Object(
+            [(variant.name.clone(), fields_to_json(&variant.values, types))]
+                .into_iter()
+                .collect(),
+        ),
+        ValueDef::BitSequence(bits) => json!(bits.iter().collect::<Vec<bool>>()),
+        ValueDef::Primitive(primitive) => primitive_to_json(primitive),
+    }
+}
+
+/// Decoded events of an extrinsic
+pub fn events_output(
+    events: &ExtrinsicEvents<PolkadotConfig>,
+    types: &PortableRegistry,
+) -> error::Result<Vec<EventOutput>> {
+    events
+        .iter()
+        .map(|event| {
+            let event = event.map_err(map_subxt_err)?;
+            let fields = event.field_values().map_err(map_subxt_err)?;
+            Ok(EventOutput {
+                pallet: event.pallet_name().to_string(),
+                variant: event.variant_name().to_string(),
+                fields: fields_to_json(&fields, types),
+            })
+        })
+        .collect()
+}
+
+/// Submit a call of any pallet with arguments encoded against
+/// the runtime metadata, reports all the events it emitted
+pub async fn tx(
+    data: web::Data<AppState>,
+    req: web::Json<DynamicTxInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for DynamicTxInput {
+    type Output = DynamicTxOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        dynamic_call_data(api, &self.pallet, &self.call, &self.args)
+    }
+
+    async fn output(
+        &self,
+        data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<DynamicTxOutput> {
+        Ok(DynamicTxOutput {
+            events: events_output(events, data.api.metadata().types())?,
+        })
+    }
+}
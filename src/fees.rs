@@ -0,0 +1,111 @@
+use crate::state::*;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use codec::Encode;
+use serde_json::json;
+use subxt::tx::Signer as _;
+use subxt::utils::MultiSignature;
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::sugarfunge::runtime_types::pallet_transaction_payment::types::{
+    FeeDetails, RuntimeDispatchInfo,
+};
+use sugarfunge_api_types::sugarfunge::runtime_types::sp_weights::weight_v2::Weight;
+use sugarfunge_api_types::tx::*;
+
+/// Signature of the right size for the scheme of the signer,
+/// fees only depend on the length of the extrinsic
+fn placeholder_signature(scheme: CryptoScheme) -> MultiSignature {
+    match scheme {
+        CryptoScheme::Sr25519 => MultiSignature::Sr25519([0; 64]),
+        CryptoScheme::Ed25519 => MultiSignature::Ed25519([0; 64]),
+        CryptoScheme::Ecdsa => MultiSignature::Ecdsa([0; 65]),
+    }
+}
+
+async fn estimate_operation<O: Operation>(
+    data: &AppState,
+    input: serde_json::Value,
+) -> error::Result<HttpResponse> {
+    let op: O = parse_operation(input)?;
+    let options = op.tx_options().cloned().unwrap_or_default();
+    let call = encode_call(&data.api, &op)?;
+    let api = &data.api;
+    let params = build_params(options_mortality(api, &options).await?, options_tip(&op));
+
+    let (account, scheme) = match op.signer().signer_account {
+        Some(_) => (
+            external_signer(op.signer())?,
+            op.signer().scheme.unwrap_or_default(),
+        ),
+        None => {
+            let signer = get_signer(data, op.signer())?;
+            (signer.account_id(), signer.scheme())
+        }
+    };
+    let nonce = match options.nonce {
+        Some(nonce) => nonce,
+        None => api
+            .tx()
+            .account_nonce(&account)
+            .await
+            .map_err(map_subxt_err)?,
+    };
+    let signature = placeholder_signature(scheme);
+    let extrinsic = api
+        .tx()
+        .create_partial_signed_with_nonce(&call, &account, nonce, params)
+        .map_err(map_subxt_err)?
+        .sign_with_address_and_signature(&account.into(), &signature);
+
+    let fee = query_fee(api, extrinsic.encoded()).await?;
+    Ok(HttpResponse::Ok().json(fee))
+}
+
+/// Fee of a signed extrinsic as computed by the transaction payment pallet
+pub async fn query_fee(api: &ClientAPI, extrinsic: &[u8]) -> error::Result<FeeEstimateOutput> {
+    let mut call_parameters = extrinsic.to_vec();
+    (extrinsic.len() as u32).encode_to(&mut call_parameters);
+    let runtime_api = api.runtime_api().at_latest().await.map_err(map_subxt_err)?;
+    let info: RuntimeDispatchInfo<u128, Weight> = runtime_api
+        .call_raw("TransactionPaymentApi_query_info", Some(&call_parameters))
+        .await
+        .map_err(map_subxt_err)?;
+    let details: FeeDetails<u128> = runtime_api
+        .call_raw(
+            "TransactionPaymentApi_query_fee_details",
+            Some(&call_parameters),
+        )
+        .await
+        .map_err(map_subxt_err)?;
+
+    Ok(FeeEstimateOutput {
+        partial_fee: info.partial_fee.into(),
+        weight: WeightOutput {
+            ref_time: info.weight.ref_time,
+            proof_size: info.weight.proof_size,
+        },
+        class: format!("{:?}", info.class),
+        inclusion_fee: details.inclusion_fee.map(|fee| InclusionFeeOutput {
+            base_fee: fee.base_fee.into(),
+            len_fee: fee.len_fee.into(),
+            adjusted_weight_fee: fee.adjusted_weight_fee.into(),
+        }),
+        tip: details.tip.into(),
+    })
+}
+
+/// Estimate the fee of an operation without submitting it
+pub async fn estimate(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    req: web::Json<serde_json::Value>,
+) -> error::Result<HttpResponse> {
+    let (module, operation) = path.into_inner();
+    let input = req.into_inner();
+    with_operation!(
+        module.as_str(),
+        operation.as_str(),
+        estimate_operation(&data, input)
+    )
+}
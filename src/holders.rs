@@ -0,0 +1,304 @@
+use crate::state::*;
+use crate::storage::KeyDecoder;
+use crate::util::*;
+use actix_web::error;
+use futures::StreamExt;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::RwLock;
+use std::time::Duration;
+use subxt::blocks::Block;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use sugarfunge_api_types::asset::IndexStatus;
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::sugarfunge;
+
+/// Wait before subscribing again after the subscription failed, doubled
+/// on each failure in a row up to `MAX_RETRY_DELAY`
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+type FinalizedBlock = Block<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+
+#[derive(Default)]
+struct Index {
+    /// Finalized block the balances are up to date with
+    block: Option<AtBlock>,
+    /// Latest finalized block seen
+    finalized: Option<u32>,
+    /// Whether the index follows the finalized blocks as they come
+    following: bool,
+    balances: HashMap<(u64, u64), HashMap<AccountId32, u128>>,
+}
+
+/// Holders of every asset, kept up to date with the finalized blocks so
+/// that they are known without scanning `Asset::Balances`, keyed by account
+#[derive(Default)]
+pub struct HolderIndex {
+    index: RwLock<Index>,
+}
+
+fn map_index_err() -> actix_web::Error {
+    let req_error = RequestError {
+        message: json!("The holder index is being built, try again shortly"),
+        description: "Holder index error".into(),
+    };
+    let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+    error::ErrorServiceUnavailable(req_error)
+}
+
+impl HolderIndex {
+    /// Build the index and follow the finalized blocks in the background
+    pub fn start(data: &AppState) {
+        actix_web::rt::spawn(run(data.clone()));
+    }
+
+    /// Holders of an asset with their amounts, along with the block they are up to date with
+    pub fn holders(
+        &self,
+        class_id: u64,
+        asset_id: u64,
+    ) -> error::Result<(Vec<(AccountId32, u128)>, IndexStatus)> {
+        let index = self.index.read().unwrap();
+        let status = index.status()?;
+        let holders = index
+            .balances
+            .get(&(class_id, asset_id))
+            .map(|holders| {
+                holders
+                    .iter()
+                    .map(|(account, amount)| (account.clone(), *amount))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok((holders, status))
+    }
+
+    /// Total amount of an asset and its number of holders
+    pub fn supply(
+        &self,
+        class_id: u64,
+        asset_id: u64,
+    ) -> error::Result<(u128, usize, IndexStatus)> {
+        let index = self.index.read().unwrap();
+        let status = index.status()?;
+        let (supply, holders) = index
+            .balances
+            .get(&(class_id, asset_id))
+            .map(|holders| {
+                let supply = holders
+                    .values()
+                    .fold(0u128, |supply, amount| supply.saturating_add(*amount));
+                (supply, holders.len())
+            })
+            .unwrap_or_default();
+        Ok((supply, holders, status))
+    }
+
+    fn block_number(&self) -> Option<u32> {
+        let index = self.index.read().unwrap();
+        index.block.as_ref().map(|block| block.block_number)
+    }
+
+    fn set_finalized(&self, number: u32) {
+        self.index.write().unwrap().finalized = Some(number);
+    }
+
+    fn set_following(&self, following: bool) {
+        self.index.write().unwrap().following = following;
+    }
+}
+
+impl Index {
+    fn status(&self) -> error::Result<IndexStatus> {
+        let block = self.block.clone().ok_or_else(map_index_err)?;
+        Ok(IndexStatus {
+            stale: !self.following || self.finalized != Some(block.block_number),
+            finalized_block_number: self.finalized,
+            block,
+        })
+    }
+}
+
+async fn run(data: AppState) {
+    let mut delay = MIN_RETRY_DELAY;
+    loop {
+        match follow(&data, &mut delay).await {
+            Ok(()) => log::info!("Holder index subscription ended"),
+            Err(e) => log::warn!("Holder index stopped following blocks: {}", e),
+        }
+        data.holders.set_following(false);
+        actix_web::rt::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+/// Follow the finalized blocks, `delay` is reset once a block is indexed
+async fn follow(data: &AppState, delay: &mut Duration) -> error::Result<()> {
+    let mut blocks = data
+        .api
+        .blocks()
+        .subscribe_finalized()
+        .await
+        .map_err(map_subxt_err)?;
+    while let Some(block) = blocks.next().await {
+        let block = block.map_err(map_subxt_err)?;
+        let number = block.number();
+        data.holders.set_finalized(number);
+        match data.holders.block_number() {
+            None => scan(data, &block).await?,
+            Some(indexed) if number <= indexed => {}
+            Some(indexed) => {
+                // Blocks finalized while not subscribed are caught up with their events,
+                // or with a scan if the node pruned them
+                match missed_balances(data, indexed + 1..number).await {
+                    Ok(mut moved) => {
+                        let events = block.events().await.map_err(map_subxt_err)?;
+                        moved.extend(moved_balances(&events).map_err(map_subxt_err)?);
+                        update(data, &block, moved).await?;
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Holder index rescanning, blocks {} to {} can't be caught up: {}",
+                            indexed + 1,
+                            number - 1,
+                            e
+                        );
+                        scan(data, &block).await?;
+                    }
+                }
+            }
+        }
+        data.holders.set_following(true);
+        *delay = MIN_RETRY_DELAY;
+    }
+    Ok(())
+}
+
+/// Build the index from all of `Asset::Balances`, once at startup
+async fn scan(data: &AppState, block: &FinalizedBlock) -> error::Result<()> {
+    let decoder = KeyDecoder::new(&data.api.metadata(), "Asset", "Balances")?;
+    let mut balances: HashMap<(u64, u64), HashMap<AccountId32, u128>> = HashMap::new();
+    let mut entries = block
+        .storage()
+        .iter(sugarfunge::storage().asset().balances_iter())
+        .await
+        .map_err(map_subxt_err)?;
+    while let Some(entry) = entries.next().await {
+        let entry = entry.map_err(map_subxt_err)?;
+        if entry.value == 0 {
+            continue;
+        }
+        let mut keys = decoder.keys(&entry.key_bytes)?;
+        let account: AccountId32 = keys.next()?;
+        let class_id: u64 = keys.next()?;
+        let asset_id: u64 = keys.next()?;
+        balances
+            .entry((class_id, asset_id))
+            .or_default()
+            .insert(account, entry.value);
+    }
+
+    let mut index = data.holders.index.write().unwrap();
+    index.balances = balances;
+    index.block = Some(at_block(block));
+    Ok(())
+}
+
+/// Balances moved by the asset events of a block
+fn moved_balances(
+    events: &subxt::events::Events<PolkadotConfig>,
+) -> Result<BTreeSet<(AccountId32, u64, u64)>, subxt::Error> {
+    let mut moved = BTreeSet::new();
+    for event in events.find::<sugarfunge::asset::events::Mint>() {
+        let event = event?;
+        moved.insert((event.to, event.class_id, event.asset_id));
+    }
+    for event in events.find::<sugarfunge::asset::events::Burn>() {
+        let event = event?;
+        moved.insert((event.from, event.class_id, event.asset_id));
+    }
+    for event in events.find::<sugarfunge::asset::events::Transferred>() {
+        let event = event?;
+        moved.insert((event.from, event.class_id, event.asset_id));
+        moved.insert((event.to, event.class_id, event.asset_id));
+    }
+    for event in events.find::<sugarfunge::asset::events::BatchMint>() {
+        let event = event?;
+        for asset_id in event.asset_ids {
+            moved.insert((event.to.clone(), event.class_id, asset_id));
+        }
+    }
+    for event in events.find::<sugarfunge::asset::events::BatchBurn>() {
+        let event = event?;
+        for asset_id in event.asset_ids {
+            moved.insert((event.from.clone(), event.class_id, asset_id));
+        }
+    }
+    for event in events.find::<sugarfunge::asset::events::BatchTransferred>() {
+        let event = event?;
+        for asset_id in event.asset_ids {
+            moved.insert((event.from.clone(), event.class_id, asset_id));
+            moved.insert((event.to.clone(), event.class_id, asset_id));
+        }
+    }
+    Ok(moved)
+}
+
+/// Balances moved by the asset events of a range of blocks
+async fn missed_balances(
+    data: &AppState,
+    blocks: std::ops::Range<u32>,
+) -> error::Result<BTreeSet<(AccountId32, u64, u64)>> {
+    let mut moved = BTreeSet::new();
+    for number in blocks {
+        let hash = data
+            .rpc
+            .chain_get_block_hash(Some(number.into()))
+            .await
+            .map_err(map_subxt_err)?
+            .ok_or_else(|| {
+                error::ErrorInternalServerError(format!("Block {} not found", number))
+            })?;
+        let block = data.api.blocks().at(hash).await.map_err(map_subxt_err)?;
+        let events = block.events().await.map_err(map_subxt_err)?;
+        moved.extend(moved_balances(&events).map_err(map_subxt_err)?);
+    }
+    Ok(moved)
+}
+
+/// Read again the moved balances at a block
+async fn update(
+    data: &AppState,
+    block: &FinalizedBlock,
+    moved: BTreeSet<(AccountId32, u64, u64)>,
+) -> error::Result<()> {
+    let storage = block.storage();
+    let mut amounts = Vec::new();
+    for (account, class_id, asset_id) in moved {
+        let call = sugarfunge::storage()
+            .asset()
+            .balances(&account, class_id, asset_id);
+        let amount = storage
+            .fetch_or_default(&call)
+            .await
+            .map_err(map_subxt_err)?;
+        amounts.push((account, class_id, asset_id, amount));
+    }
+
+    let mut index = data.holders.index.write().unwrap();
+    for (account, class_id, asset_id, amount) in amounts {
+        let holders = index.balances.entry((class_id, asset_id)).or_default();
+        if amount == 0 {
+            holders.remove(&account);
+            if holders.is_empty() {
+                index.balances.remove(&(class_id, asset_id));
+            }
+        } else {
+            holders.insert(account, amount);
+        }
+    }
+    index.block = Some(at_block(block));
+    Ok(())
+}
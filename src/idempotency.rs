@@ -0,0 +1,293 @@
+use crate::state::*;
+use crate::util::*;
+use actix_web::body::{self, BoxBody};
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use actix_web::http::StatusCode;
+use actix_web::web::{Bytes, Data};
+use actix_web::{error, Error, HttpResponse};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header carrying the hash of the submitted extrinsic
+pub const EXTRINSIC_HASH_HEADER: &str = "x-extrinsic-hash";
+const EXTRINSIC_HASH_NAME: HeaderName = HeaderName::from_static(EXTRINSIC_HASH_HEADER);
+
+/// Set on responses replayed from an earlier request with the same key
+const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+enum Outcome {
+    /// Being handled, with the hash of its extrinsic once submitted
+    InProgress { extrinsic_hash: Option<String> },
+    /// Submitted, but the response was lost, e.g. when the client went away
+    Submitted { extrinsic_hash: String },
+    Done {
+        status: StatusCode,
+        content_type: Option<HeaderValue>,
+        extrinsic_hash: Option<HeaderValue>,
+        body: Bytes,
+    },
+}
+
+struct Record {
+    path: String,
+    body_hash: [u8; 32],
+    created: Instant,
+    outcome: Outcome,
+}
+
+/// Outcome of the requests sent with an `Idempotency-Key` header, kept for
+/// the retention window so that retries don't submit twice
+pub struct IdempotencyStore {
+    retention: Duration,
+    records: Mutex<HashMap<String, Record>>,
+}
+
+tokio::task_local! {
+    /// Store and key of the request being handled with an `Idempotency-Key`
+    static CURRENT: (Arc<IdempotencyStore>, String);
+}
+
+/// Record that the request being handled submitted its extrinsic, so that
+/// a retry with the same key is never submitted again
+pub fn submitted(extrinsic_hash: &str) {
+    let _ = CURRENT.try_with(|(store, key)| {
+        if let Some(record) = store.records.lock().unwrap().get_mut(key) {
+            if let Outcome::InProgress { .. } = record.outcome {
+                record.outcome = Outcome::InProgress {
+                    extrinsic_hash: Some(extrinsic_hash.to_string()),
+                };
+            }
+        }
+    });
+}
+
+/// Forgets a request that did not complete before submitting anything, or
+/// keeps the hash of the extrinsic it submitted
+struct InProgress {
+    store: Arc<IdempotencyStore>,
+    key: String,
+    done: bool,
+}
+
+impl Drop for InProgress {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let mut records = self.store.records.lock().unwrap();
+        let Some(record) = records.get_mut(&self.key) else {
+            return;
+        };
+        match &record.outcome {
+            Outcome::InProgress {
+                extrinsic_hash: Some(extrinsic_hash),
+            } => {
+                record.outcome = Outcome::Submitted {
+                    extrinsic_hash: extrinsic_hash.clone(),
+                };
+            }
+            Outcome::InProgress {
+                extrinsic_hash: None,
+            } => {
+                records.remove(&self.key);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn idempotency_err(status: StatusCode, message: &str) -> HttpResponse {
+    HttpResponse::build(status).json(RequestError {
+        message: json!(message),
+        description: "Idempotency error".into(),
+    })
+}
+
+impl IdempotencyStore {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Response of an earlier request with the same key, or `None` if the
+    /// request is new and now recorded as in progress
+    fn begin(&self, key: &str, path: &str, body_hash: [u8; 32]) -> Option<HttpResponse> {
+        let mut records = self.records.lock().unwrap();
+        records.retain(|_, record| record.created.elapsed() < self.retention);
+        let Some(record) = records.get(key) else {
+            records.insert(
+                key.to_string(),
+                Record {
+                    path: path.to_string(),
+                    body_hash,
+                    created: Instant::now(),
+                    outcome: Outcome::InProgress {
+                        extrinsic_hash: None,
+                    },
+                },
+            );
+            return None;
+        };
+        if record.path != path {
+            return Some(idempotency_err(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key was already used for another endpoint",
+            ));
+        }
+        if record.body_hash != body_hash {
+            return Some(idempotency_err(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key was already used with another request body",
+            ));
+        }
+        match &record.outcome {
+            Outcome::InProgress { extrinsic_hash } => {
+                let mut res = idempotency_err(
+                    StatusCode::CONFLICT,
+                    "A request with this Idempotency-Key is still in progress",
+                );
+                if let Some(extrinsic_hash) = extrinsic_hash {
+                    if let Ok(value) = HeaderValue::from_str(extrinsic_hash) {
+                        res.headers_mut().insert(EXTRINSIC_HASH_NAME, value);
+                    }
+                }
+                Some(res)
+            }
+            Outcome::Submitted { extrinsic_hash } => Some(
+                HttpResponse::Accepted()
+                    .insert_header((REPLAYED_HEADER, "true"))
+                    .insert_header((EXTRINSIC_HASH_HEADER, extrinsic_hash.clone()))
+                    .json(RequestError {
+                        message: json!(format!(
+                            "The request submitted extrinsic {} but its outcome was not recorded",
+                            extrinsic_hash
+                        )),
+                        description: "Idempotency error".into(),
+                    }),
+            ),
+            Outcome::Done {
+                status,
+                content_type,
+                extrinsic_hash,
+                body,
+            } => {
+                let mut res = HttpResponse::build(*status);
+                res.insert_header((REPLAYED_HEADER, "true"));
+                if let Some(content_type) = content_type {
+                    res.insert_header((CONTENT_TYPE, content_type.clone()));
+                }
+                if let Some(extrinsic_hash) = extrinsic_hash {
+                    res.insert_header((EXTRINSIC_HASH_HEADER, extrinsic_hash.clone()));
+                }
+                Some(res.body(body.clone()))
+            }
+        }
+    }
+
+    fn complete(&self, key: &str, outcome: Outcome) {
+        if let Some(record) = self.records.lock().unwrap().get_mut(key) {
+            record.outcome = outcome;
+        }
+    }
+}
+
+/// Replays the outcome of requests retried with the same `Idempotency-Key`
+/// and body, failed responses included
+pub struct Idempotency;
+
+impl<S> Transform<S, ServiceRequest> for Idempotency
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = IdempotencyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IdempotencyMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct IdempotencyMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S> Service<ServiceRequest> for IdempotencyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<ServiceResponse<BoxBody>, Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            let key = req
+                .headers()
+                .get(IDEMPOTENCY_KEY_HEADER)
+                .and_then(|key| key.to_str().ok())
+                .map(String::from);
+            let store = req
+                .app_data::<Data<AppState>>()
+                .map(|data| data.idempotency.clone());
+            let (Some(key), Some(store)) = (key, store) else {
+                return service.call(req).await;
+            };
+
+            // The body is read here, and given back to the handler
+            let body = req.extract::<Bytes>().await?;
+            let body_hash: [u8; 32] = Sha256::digest(&body).into();
+            req.set_payload(Payload::from(body));
+
+            if let Some(res) = store.begin(&key, req.path(), body_hash) {
+                return Ok(req.into_response(res));
+            }
+            let mut in_progress = InProgress {
+                store: store.clone(),
+                key: key.clone(),
+                done: false,
+            };
+
+            let res = CURRENT.scope((store, key), service.call(req)).await?;
+            let status = res.status();
+            let content_type = res.headers().get(CONTENT_TYPE).cloned();
+            let extrinsic_hash = res.headers().get(EXTRINSIC_HASH_HEADER).cloned();
+            let (req, res) = res.into_parts();
+            let (res, body) = res.into_parts();
+            let body = body::to_bytes(body)
+                .await
+                .map_err(|e| error::ErrorInternalServerError(e.to_string()))?;
+
+            in_progress.store.complete(
+                &in_progress.key,
+                Outcome::Done {
+                    status,
+                    content_type,
+                    extrinsic_hash,
+                    body: body.clone(),
+                },
+            );
+            in_progress.done = true;
+            Ok(ServiceResponse::new(req, res.set_body(BoxBody::new(body))))
+        })
+    }
+}
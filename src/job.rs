@@ -0,0 +1,537 @@
+use crate::state::*;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use futures::stream::{self, StreamExt};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use sugarfunge_api_types::asset::{BatchMintInput, BatchTransferFromInput};
+use sugarfunge_api_types::job::*;
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::validation::FieldError;
+
+/// Default number of assets minted or transferred per extrinsic
+const DEFAULT_CHUNK_SIZE: usize = 50;
+
+/// Extrinsics of a job awaiting their outcome at the same time
+const MAX_IN_FLIGHT: usize = 16;
+
+/// Job as stored on disk
+#[derive(Serialize, Deserialize)]
+struct JobFile {
+    job_id: String,
+    mode: AirdropMode,
+    from: Option<Account>,
+    signer: Signer,
+    tx_options: Option<TxOptions>,
+    chunk_size: usize,
+    rows: Vec<JobRowOutput>,
+}
+
+struct Job {
+    file: JobFile,
+    running: bool,
+    /// Changed since it was last written
+    dirty: bool,
+    /// Held while the job is written, so that an older state never
+    /// overwrites a newer one
+    save_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Last failure to save the job, cleared by the next save
+    save_error: Option<String>,
+}
+
+impl Job {
+    fn new(file: JobFile) -> Self {
+        Job {
+            file,
+            running: false,
+            dirty: true,
+            save_lock: Arc::default(),
+            save_error: None,
+        }
+    }
+}
+
+/// Airdrop jobs, persisted one file per job so that they resume on restart
+pub struct JobManager {
+    path: PathBuf,
+    jobs: Mutex<HashMap<String, Arc<Mutex<Job>>>>,
+}
+
+fn map_job_err(message: &str) -> actix_web::Error {
+    let req_error = RequestError {
+        message: json!(message),
+        description: "Job error".into(),
+    };
+    let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+    error::ErrorBadRequest(req_error)
+}
+
+fn job_output(job: &Job) -> JobOutput {
+    let rows = &job.file.rows;
+    let count = |status| rows.iter().filter(|row| row.status == status).count();
+    let pending = count(JobRowStatus::Pending) + count(JobRowStatus::Submitted);
+    let failed = count(JobRowStatus::Failed);
+    let unknown = count(JobRowStatus::Unknown);
+    let state = if job.running || pending > 0 {
+        JobState::Running
+    } else if failed > 0 || unknown > 0 {
+        JobState::Failed
+    } else {
+        JobState::Completed
+    };
+    JobOutput {
+        job_id: job.file.job_id.clone(),
+        state,
+        total: rows.len(),
+        pending,
+        succeeded: count(JobRowStatus::Succeeded),
+        failed,
+        unknown,
+        save_error: job.save_error.clone(),
+        rows: rows.clone(),
+    }
+}
+
+fn parse_csv_line(line: &str) -> Option<AirdropRow> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [account, class_id, asset_id, amount] = fields.as_slice() else {
+        return None;
+    };
+    Some(AirdropRow {
+        account: Account::from(account.to_string()),
+        class_id: ClassId::from(class_id.parse::<u64>().ok()?),
+        asset_id: AssetId::from(asset_id.parse::<u64>().ok()?),
+        amount: Balance::from(amount.parse::<u128>().ok()?),
+    })
+}
+
+/// Rows of `account,class_id,asset_id,amount` lines, with an optional
+/// header, along with their field path `csv[line]`
+fn parse_csv(csv: &str, errors: &mut Vec<FieldError>) -> Vec<(String, AirdropRow)> {
+    let mut rows = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.starts_with("account")) {
+            continue;
+        }
+        let field = format!("csv[{}]", index);
+        match parse_csv_line(line) {
+            Some(row) => rows.push((field, row)),
+            None => errors.push(FieldError {
+                field,
+                reason: "expected account,class_id,asset_id,amount".into(),
+            }),
+        }
+    }
+    rows
+}
+
+/// Pending rows grouped by account and class, in chunks of one extrinsic
+fn plan_chunks(file: &JobFile) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of: HashMap<(String, u64), usize> = HashMap::new();
+    for (index, row) in file.rows.iter().enumerate() {
+        if row.status != JobRowStatus::Pending {
+            continue;
+        }
+        let key = (
+            row.item.account.as_str().to_string(),
+            u64::from(row.item.class_id),
+        );
+        let group = *group_of.entry(key).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(index);
+    }
+    groups
+        .into_iter()
+        .flat_map(|group| {
+            group
+                .chunks(file.chunk_size)
+                .map(<[usize]>::to_vec)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+impl JobManager {
+    /// Open the jobs directory, creating it if needed. The outcome of rows
+    /// submitted before a restart is unknown, unreadable job files are
+    /// renamed with a `.corrupt` extension and skipped.
+    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+        fs::create_dir_all(&path)?;
+        let mut jobs = HashMap::new();
+        for entry in fs::read_dir(&path)? {
+            let entry_path = entry?.path();
+            if entry_path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            let file = fs::read(&entry_path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| {
+                    serde_json::from_slice::<JobFile>(&contents).map_err(|e| e.to_string())
+                });
+            let mut file = match file {
+                Ok(file) => file,
+                Err(e) => {
+                    log::warn!("Skipping unreadable job {}: {}", entry_path.display(), e);
+                    fs::rename(&entry_path, entry_path.with_extension("json.corrupt"))?;
+                    continue;
+                }
+            };
+            for row in file.rows.iter_mut() {
+                if row.status == JobRowStatus::Submitted {
+                    row.status = JobRowStatus::Unknown;
+                    row.error = Some(json!(
+                        "Interrupted before the outcome was known, check the extrinsic hash"
+                    ));
+                }
+            }
+            let job = Job::new(file);
+            jobs.insert(job.file.job_id.clone(), Arc::new(Mutex::new(job)));
+        }
+        Ok(Self {
+            path,
+            jobs: Mutex::new(jobs),
+        })
+    }
+
+    /// Write the job to a temporary file on the blocking thread pool and
+    /// rename it over the previous one, recording any failure in the job.
+    /// Saves waiting on one another are written once, by the first of them.
+    async fn save(&self, job: &Mutex<Job>) -> Result<(), String> {
+        let save_lock = job.lock().unwrap().save_lock.clone();
+        let _saving = save_lock.lock().await;
+        let (job_id, contents) = {
+            let mut job = job.lock().unwrap();
+            if !job.dirty {
+                return match &job.save_error {
+                    Some(e) => Err(e.clone()),
+                    None => Ok(()),
+                };
+            }
+            job.dirty = false;
+            (
+                job.file.job_id.clone(),
+                serde_json::to_vec(&job.file).unwrap(),
+            )
+        };
+        let path = self.path.join(format!("{}.json", job_id));
+        let written = web::block(move || {
+            let tmp_path = path.with_extension("json.tmp");
+            fs::write(&tmp_path, contents)?;
+            fs::rename(&tmp_path, &path)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|written| written.map_err(|e| e.to_string()));
+        let mut job = job.lock().unwrap();
+        job.save_error = written.as_ref().err().cloned();
+        // Written again by the next save
+        job.dirty |= written.is_err();
+        written
+    }
+
+    fn get(&self, job_id: &str) -> error::Result<Arc<Mutex<Job>>> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(job_id)
+            .cloned()
+            .ok_or_else(|| map_job_err("Job not found"))
+    }
+
+    /// Run the pending rows of the jobs interrupted by a restart
+    pub fn resume(data: &AppState) {
+        let jobs: Vec<_> = data.jobs.jobs.lock().unwrap().values().cloned().collect();
+        for job in jobs {
+            start(data, job);
+        }
+    }
+}
+
+/// Run the pending rows of a job unless it is already running
+fn start(data: &AppState, job: Arc<Mutex<Job>>) {
+    {
+        let mut job = job.lock().unwrap();
+        if job.running
+            || !job
+                .file
+                .rows
+                .iter()
+                .any(|row| row.status == JobRowStatus::Pending)
+        {
+            return;
+        }
+        job.running = true;
+    }
+    actix_web::rt::spawn(run(data.clone(), job));
+}
+
+async fn run(data: AppState, job: Arc<Mutex<Job>>) {
+    loop {
+        let chunks = {
+            let mut job = job.lock().unwrap();
+            let chunks = plan_chunks(&job.file);
+            if chunks.is_empty() {
+                job.running = false;
+                return;
+            }
+            chunks
+        };
+        stream::iter(chunks)
+            .for_each_concurrent(MAX_IN_FLIGHT, |rows| run_chunk(&data, &job, rows))
+            .await;
+    }
+}
+
+/// Update rows of a job and save it, a failure to save is reported by the
+/// job status
+async fn update_rows(
+    data: &AppState,
+    job: &Mutex<Job>,
+    rows: &[usize],
+    update: impl Fn(&mut JobRowOutput),
+) {
+    {
+        let mut job = job.lock().unwrap();
+        for row in rows {
+            update(&mut job.file.rows[*row]);
+        }
+        job.dirty = true;
+    }
+    let _ = data.jobs.save(job).await;
+}
+
+async fn run_chunk(data: &AppState, job: &Mutex<Job>, rows: Vec<usize>) {
+    let (mode, from, signer, tx_options, to, class_id, asset_ids, amounts) = {
+        let job = job.lock().unwrap();
+        let file = &job.file;
+        let first = &file.rows[rows[0]].item;
+        (
+            file.mode,
+            file.from.clone(),
+            file.signer.clone(),
+            file.tx_options.clone(),
+            first.account.clone(),
+            first.class_id,
+            rows.iter()
+                .map(|row| file.rows[*row].item.asset_id)
+                .collect(),
+            rows.iter().map(|row| file.rows[*row].item.amount).collect(),
+        )
+    };
+    match (mode, from) {
+        (AirdropMode::Mint, _) => {
+            let op = BatchMintInput {
+                signer,
+                tx_options,
+                to,
+                class_id,
+                asset_ids,
+                amounts,
+            };
+            run_batch(data, job, &rows, op).await
+        }
+        (AirdropMode::Transfer, Some(from)) => {
+            let op = BatchTransferFromInput {
+                signer,
+                tx_options,
+                from,
+                to,
+                class_id,
+                asset_ids,
+                amounts,
+            };
+            run_batch(data, job, &rows, op).await
+        }
+        (AirdropMode::Transfer, None) => {
+            update_rows(data, job, &rows, |row| {
+                row.status = JobRowStatus::Failed;
+                row.error = Some(json!("from is required to transfer"));
+            })
+            .await
+        }
+    }
+}
+
+/// Submit the batch extrinsic of a chunk and record its outcome in its rows
+async fn run_batch<O: Operation>(data: &AppState, job: &Mutex<Job>, rows: &[usize], op: O) {
+    let (progress, account) = match sign_and_submit(data, &op).await {
+        Ok(submitted) => submitted,
+        Err(e) => {
+            let error = error_value(e);
+            return update_rows(data, job, rows, |row| {
+                row.status = JobRowStatus::Failed;
+                row.error = Some(error.clone());
+            })
+            .await;
+        }
+    };
+    let extrinsic_hash = format!("{:?}", progress.extrinsic_hash());
+    update_rows(data, job, rows, |row| {
+        row.status = JobRowStatus::Submitted;
+        row.extrinsic_hash = Some(extrinsic_hash.clone());
+        row.block_hash = None;
+        row.error = None;
+    })
+    .await;
+
+    let wait = op
+        .tx_options()
+        .and_then(|options| options.wait)
+        .unwrap_or_default();
+    match wait_for_events(data, progress, wait, &account).await {
+        Ok(events) => {
+            let block_hash = format!("{:?}", events.block_hash());
+            update_rows(data, job, rows, |row| {
+                row.status = JobRowStatus::Succeeded;
+                row.block_hash = Some(block_hash.clone());
+            })
+            .await
+        }
+        Err(e) => {
+            let error = error_value(e);
+            update_rows(data, job, rows, |row| {
+                row.status = JobRowStatus::Failed;
+                row.error = Some(error.clone());
+            })
+            .await
+        }
+    }
+}
+
+/// Start a job minting or transferring assets to a list of accounts
+pub async fn airdrop(
+    data: web::Data<AppState>,
+    req: web::Json<AirdropInput>,
+) -> error::Result<HttpResponse> {
+    let req = req.into_inner();
+    let mut errors = Vec::new();
+    if req.signer.seed.is_some() {
+        errors.push(FieldError {
+            field: "seed".into(),
+            reason: "jobs are persisted, sign them with a keystore key".into(),
+        });
+    }
+    if req.signer.password.is_some() {
+        errors.push(FieldError {
+            field: "password".into(),
+            reason: "jobs are persisted, sign them with a key without password".into(),
+        });
+    }
+    if req
+        .tx_options
+        .as_ref()
+        .map_or(false, |options| options.nonce.is_some())
+    {
+        errors.push(FieldError {
+            field: "tx_options.nonce".into(),
+            reason: "jobs submit many extrinsics, their nonces are assigned by the API".into(),
+        });
+    }
+    if req.mode == AirdropMode::Transfer && req.from.is_none() {
+        errors.push(FieldError {
+            field: "from".into(),
+            reason: "required to transfer".into(),
+        });
+    }
+
+    let mut items: Vec<(String, AirdropRow)> = req
+        .rows
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| (format!("rows[{}]", index), row))
+        .collect();
+    if let Some(csv) = &req.csv {
+        items.extend(parse_csv(csv, &mut errors));
+    }
+    if items.is_empty() && errors.is_empty() {
+        errors.push(FieldError {
+            field: "rows".into(),
+            reason: "no rows to distribute".into(),
+        });
+    }
+    for (field, item) in &items {
+        if subxt::utils::AccountId32::try_from(&item.account).is_err() {
+            errors.push(FieldError {
+                field: format!("{}.account", field),
+                reason: "invalid account".into(),
+            });
+        }
+    }
+    if !errors.is_empty() {
+        return Err(map_validation_err(errors));
+    }
+    get_signer(&data, &req.signer)?;
+
+    let job_id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+    let file = JobFile {
+        job_id: job_id.clone(),
+        mode: req.mode,
+        from: req.from,
+        signer: req.signer,
+        tx_options: req.tx_options,
+        chunk_size: req.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE).max(1),
+        rows: items
+            .into_iter()
+            .enumerate()
+            .map(|(row, (_, item))| JobRowOutput {
+                row,
+                item,
+                status: JobRowStatus::Pending,
+                extrinsic_hash: None,
+                block_hash: None,
+                error: None,
+            })
+            .collect(),
+    };
+    let job = Arc::new(Mutex::new(Job::new(file)));
+    data.jobs.save(&job).await.map_err(|e| map_job_err(&e))?;
+    data.jobs.jobs.lock().unwrap().insert(job_id, job.clone());
+    start(&data, job.clone());
+    let output = job_output(&job.lock().unwrap());
+    Ok(HttpResponse::Ok().json(output))
+}
+
+/// Get the status of a job and the result of each of its rows
+pub async fn status(
+    data: web::Data<AppState>,
+    req: web::Json<JobInput>,
+) -> error::Result<HttpResponse> {
+    let job = data.jobs.get(&req.job_id)?;
+    let output = job_output(&job.lock().unwrap());
+    Ok(HttpResponse::Ok().json(output))
+}
+
+/// Run the failed rows of a job again
+pub async fn retry(
+    data: web::Data<AppState>,
+    req: web::Json<RetryJobInput>,
+) -> error::Result<HttpResponse> {
+    let job = data.jobs.get(&req.job_id)?;
+    {
+        let mut job = job.lock().unwrap();
+        for row in job.file.rows.iter_mut() {
+            if row.status == JobRowStatus::Failed
+                || (req.include_unknown && row.status == JobRowStatus::Unknown)
+            {
+                row.status = JobRowStatus::Pending;
+                row.error = None;
+            }
+        }
+        job.dirty = true;
+    }
+    data.jobs.save(&job).await.map_err(|e| map_job_err(&e))?;
+    start(&data, job.clone());
+    let output = job_output(&job.lock().unwrap());
+    Ok(HttpResponse::Ok().json(output))
+}
@@ -0,0 +1,363 @@
+use crate::state::*;
+use crate::util::*;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{error, web, HttpRequest, HttpResponse};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use subxt::tx::Signer as _;
+use sugarfunge_api_types::keystore::*;
+use sugarfunge_api_types::primitives::*;
+
+/// PBKDF2 rounds used to derive the encryption key from the passphrase
+const PBKDF2_ROUNDS: u32 = 100_000;
+
+/// Key as stored on disk, only the secret is encrypted
+#[derive(Serialize, Deserialize, Clone)]
+struct KeyFile {
+    key_id: String,
+    account: String,
+    #[serde(default)]
+    scheme: CryptoScheme,
+    salt: String,
+    nonce: String,
+    secret: String,
+    /// Encryption key derived from the passphrase and the salt
+    #[serde(skip)]
+    cipher_key: Option<[u8; 32]>,
+}
+
+/// Signing keys encrypted at rest under a single passphrase, one file per key
+pub struct Keystore {
+    path: PathBuf,
+    passphrase: Option<String>,
+    /// Bearer token required to manage the keys, disabled without one
+    token: Option<String>,
+    /// Whether operations may be signed with the stored keys
+    signing: bool,
+    keys: RwLock<HashMap<String, KeyFile>>,
+    /// Key ids being inserted, reserved until their file is written
+    inserting: Mutex<HashSet<String>>,
+}
+
+/// Releases a key id reserved for insertion
+struct Reservation<'a> {
+    inserting: &'a Mutex<HashSet<String>>,
+    key_id: String,
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        self.inserting.lock().unwrap().remove(&self.key_id);
+    }
+}
+
+fn map_keystore_err(message: &str) -> actix_web::Error {
+    let req_error = RequestError {
+        message: json!(message),
+        description: "Keystore error".into(),
+    };
+    let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+    error::ErrorBadRequest(req_error)
+}
+
+fn map_keystore_auth_err(message: &str, unauthorized: bool) -> actix_web::Error {
+    let req_error = RequestError {
+        message: json!(message),
+        description: "Keystore error".into(),
+    };
+    let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+    if unauthorized {
+        error::ErrorUnauthorized(req_error)
+    } else {
+        error::ErrorForbidden(req_error)
+    }
+}
+
+/// Derive the encryption key of a key file, slow on purpose
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+
+fn key_output(file: &KeyFile) -> KeyOutput {
+    KeyOutput {
+        key_id: KeyId::from(file.key_id.clone()),
+        account: Account::from(file.account.clone()),
+        scheme: file.scheme,
+    }
+}
+
+impl Keystore {
+    /// Open the keystore directory, creating it if needed, and derive the
+    /// encryption keys of the stored keys once. Unreadable key files are
+    /// renamed with a `.corrupt` extension and skipped.
+    pub fn open(
+        path: PathBuf,
+        passphrase: Option<String>,
+        token: Option<String>,
+        signing: bool,
+    ) -> std::io::Result<Self> {
+        fs::create_dir_all(&path)?;
+        let mut keys = HashMap::new();
+        for entry in fs::read_dir(&path)? {
+            let entry_path = entry?.path();
+            if entry_path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            let file = fs::read(&entry_path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| {
+                    serde_json::from_slice::<KeyFile>(&contents).map_err(|e| e.to_string())
+                });
+            let mut file = match file {
+                Ok(file) => file,
+                Err(e) => {
+                    log::warn!("Skipping unreadable key {}: {}", entry_path.display(), e);
+                    fs::rename(&entry_path, entry_path.with_extension("json.corrupt"))?;
+                    continue;
+                }
+            };
+            if let (Some(passphrase), Ok(salt)) = (&passphrase, hex::decode(&file.salt)) {
+                file.cipher_key = Some(derive_key(passphrase, &salt));
+            }
+            keys.insert(file.key_id.clone(), file);
+        }
+        Ok(Self {
+            path,
+            passphrase,
+            token,
+            signing,
+            keys: RwLock::new(keys),
+            inserting: Mutex::default(),
+        })
+    }
+
+    /// Check the bearer token of a key management request
+    pub fn authorize(&self, req: &HttpRequest) -> error::Result<()> {
+        let token = self.token.as_ref().ok_or_else(|| {
+            map_keystore_auth_err(
+                "Keystore management is disabled, start with --keystore-token",
+                false,
+            )
+        })?;
+        let given = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        // Digests are compared so that the time taken doesn't tell the token
+        if Sha256::digest(given.as_bytes()) != Sha256::digest(token.as_bytes()) {
+            return Err(map_keystore_auth_err("Invalid keystore token", true));
+        }
+        Ok(())
+    }
+
+    fn cipher(&self, file: &KeyFile) -> error::Result<ChaCha20Poly1305> {
+        let key = file
+            .cipher_key
+            .as_ref()
+            .ok_or_else(|| map_keystore_err("Keystore passphrase is not configured"))?;
+        Ok(ChaCha20Poly1305::new(Key::from_slice(key)))
+    }
+
+    /// Encrypt and store a seed under the given key id or a random one,
+    /// deriving its encryption key on the blocking thread pool
+    pub async fn insert(
+        &self,
+        key_id: Option<&KeyId>,
+        seed: &Seed,
+        scheme: CryptoScheme,
+    ) -> error::Result<KeyOutput> {
+        let key_id = match key_id {
+            Some(key_id) => key_id.as_str().to_string(),
+            None => hex::encode(rand::thread_rng().gen::<[u8; 16]>()),
+        };
+        if key_id.is_empty()
+            || !key_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(map_keystore_err(
+                "Key id may only contain alphanumeric characters, '-' and '_'",
+            ));
+        }
+        let passphrase = self
+            .passphrase
+            .as_ref()
+            .ok_or_else(|| map_keystore_err("Keystore passphrase is not configured"))?;
+        let _reservation = {
+            let keys = self.keys.read().unwrap();
+            let mut inserting = self.inserting.lock().unwrap();
+            if keys.contains_key(&key_id) || !inserting.insert(key_id.clone()) {
+                return Err(map_keystore_err("Key id already exists"));
+            }
+            Reservation {
+                inserting: &self.inserting,
+                key_id: key_id.clone(),
+            }
+        };
+
+        let account = get_signer_from_suri(seed, None, None, scheme)?.account_id();
+
+        let salt = rand::thread_rng().gen::<[u8; 16]>();
+        let nonce = rand::thread_rng().gen::<[u8; 12]>();
+        let passphrase = passphrase.clone();
+        let cipher_key = web::block(move || derive_key(&passphrase, &salt))
+            .await
+            .map_err(error::ErrorInternalServerError)?;
+        let secret = ChaCha20Poly1305::new(Key::from_slice(&cipher_key))
+            .encrypt(Nonce::from_slice(&nonce), seed.as_str().as_bytes())
+            .map_err(|_| map_keystore_err("Failed to encrypt key"))?;
+
+        let file = KeyFile {
+            key_id: key_id.clone(),
+            account: account.to_string(),
+            scheme,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            secret: hex::encode(secret),
+            cipher_key: Some(cipher_key),
+        };
+
+        // Written to a temporary file renamed over, on the blocking thread pool
+        let contents = serde_json::to_vec_pretty(&file).unwrap();
+        let path = self.path.join(format!("{}.json", key_id));
+        web::block(move || {
+            let tmp_path = path.with_extension("json.tmp");
+            fs::write(&tmp_path, contents)?;
+            fs::rename(&tmp_path, &path)
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|e| map_keystore_err(&e.to_string()))?;
+
+        let output = key_output(&file);
+        self.keys.write().unwrap().insert(key_id, file);
+        Ok(output)
+    }
+
+    pub fn list(&self) -> Vec<KeyOutput> {
+        let mut keys: Vec<KeyOutput> = self.keys.read().unwrap().values().map(key_output).collect();
+        keys.sort_by(|a, b| a.key_id.as_str().cmp(b.key_id.as_str()));
+        keys
+    }
+
+    pub fn remove(&self, key_id: &KeyId) -> error::Result<KeyOutput> {
+        let file = self
+            .keys
+            .write()
+            .unwrap()
+            .remove(key_id.as_str())
+            .ok_or_else(|| map_keystore_err("Key not found"))?;
+        fs::remove_file(self.path.join(format!("{}.json", file.key_id)))
+            .map_err(|e| map_keystore_err(&e.to_string()))?;
+        Ok(key_output(&file))
+    }
+
+    fn decrypt(&self, file: &KeyFile) -> error::Result<(Seed, CryptoScheme)> {
+        if !self.signing {
+            return Err(map_keystore_auth_err(
+                "Signing with keystore keys is disabled, start with --keystore-signing",
+                false,
+            ));
+        }
+        let nonce = hex::decode(&file.nonce).map_err(|_| map_keystore_err("Corrupted key file"))?;
+        let secret =
+            hex::decode(&file.secret).map_err(|_| map_keystore_err("Corrupted key file"))?;
+        let seed = self
+            .cipher(file)?
+            .decrypt(Nonce::from_slice(&nonce), secret.as_slice())
+            .map_err(|_| map_keystore_err("Failed to decrypt key, wrong passphrase?"))?;
+        let seed = String::from_utf8(seed).map_err(|_| map_keystore_err("Corrupted key file"))?;
+        Ok((Seed::from(seed), file.scheme))
+    }
+
+    pub fn seed_by_key_id(&self, key_id: &KeyId) -> error::Result<(Seed, CryptoScheme)> {
+        let file = self
+            .keys
+            .read()
+            .unwrap()
+            .get(key_id.as_str())
+            .cloned()
+            .ok_or_else(|| map_keystore_err("Key not found"))?;
+        self.decrypt(&file)
+    }
+
+    pub fn seed_by_account(&self, account: &Account) -> error::Result<(Seed, CryptoScheme)> {
+        let account = subxt::utils::AccountId32::try_from(account)
+            .map_err(map_account_err)?
+            .to_string();
+        let file = self
+            .keys
+            .read()
+            .unwrap()
+            .values()
+            .find(|file| file.account == account)
+            .cloned()
+            .ok_or_else(|| map_keystore_err("No key found for account"))?;
+        self.decrypt(&file)
+    }
+}
+
+/// Generate a new key and store it in the keystore
+pub async fn generate(
+    data: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: web::Json<GenerateKeyInput>,
+) -> error::Result<HttpResponse> {
+    data.keystore.authorize(&http_req)?;
+    let seed = rand::thread_rng().gen::<[u8; 32]>();
+    let seed = Seed::from(format!("0x{}", hex::encode(seed)));
+    let key = data
+        .keystore
+        .insert(req.key_id.as_ref(), &seed, req.scheme.unwrap_or_default())
+        .await?;
+    Ok(HttpResponse::Ok().json(key))
+}
+
+/// Import an existing seed into the keystore
+pub async fn import(
+    data: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: web::Json<ImportKeyInput>,
+) -> error::Result<HttpResponse> {
+    data.keystore.authorize(&http_req)?;
+    let key = data
+        .keystore
+        .insert(
+            req.key_id.as_ref(),
+            &req.seed,
+            req.scheme.unwrap_or_default(),
+        )
+        .await?;
+    Ok(HttpResponse::Ok().json(key))
+}
+
+/// List the keys held by the keystore
+pub async fn list(data: web::Data<AppState>, http_req: HttpRequest) -> error::Result<HttpResponse> {
+    data.keystore.authorize(&http_req)?;
+    Ok(HttpResponse::Ok().json(ListKeysOutput {
+        keys: data.keystore.list(),
+    }))
+}
+
+/// Remove a key from the keystore
+pub async fn remove(
+    data: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: web::Json<RemoveKeyInput>,
+) -> error::Result<HttpResponse> {
+    data.keystore.authorize(&http_req)?;
+    let key = data.keystore.remove(&req.key_id)?;
+    Ok(HttpResponse::Ok().json(key))
+}
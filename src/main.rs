@@ -6,9 +6,17 @@ use actix_web::{
 };
 use args::*;
 use clap::Parser;
+use holders::HolderIndex;
+use idempotency::IdempotencyStore;
+use job::JobManager;
+use keystore::Keystore;
+use nonce::NonceManager;
 use state::*;
 use std::sync::Arc;
+use std::time::Duration;
+use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
 use subxt::{client::OnlineClient, PolkadotConfig};
+use tracker::TxTracker;
 use util::url_to_string;
 
 mod account;
@@ -16,10 +24,26 @@ mod args;
 mod asset;
 mod bag;
 mod bundle;
+mod chain;
+mod council;
+mod dry_run;
+mod dynamic;
+mod fees;
+mod holders;
+mod idempotency;
+mod job;
+mod keystore;
 mod market;
+mod nonce;
+mod scheduler;
 mod state;
+mod storage;
 mod subscription;
+mod sudo;
+mod tracker;
+mod tx;
 mod util;
+mod validation;
 mod validator;
 
 #[actix_web::main]
@@ -28,11 +52,36 @@ async fn main() -> std::io::Result<()> {
 
     let args = Args::parse();
 
-    let api = OnlineClient::<PolkadotConfig>::from_url(url_to_string(args.node_server))
+    let rpc_client = RpcClient::from_url(url_to_string(args.node_server))
         .await
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    let api = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc_client.clone())
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    let keystore = Keystore::open(
+        args.keystore,
+        args.keystore_passphrase,
+        args.keystore_token,
+        args.keystore_signing,
+    )?;
+    let jobs = JobManager::open(args.jobs)?;
+
+    let state = AppState {
+        api: Arc::new(api),
+        rpc: LegacyRpcMethods::new(rpc_client),
+        keystore: Arc::new(keystore),
+        nonces: Arc::new(NonceManager::default()),
+        tracker: Arc::new(TxTracker::default()),
+        idempotency: Arc::new(IdempotencyStore::new(Duration::from_secs(
+            args.idempotency_retention,
+        ))),
+        jobs: Arc::new(jobs),
+        holders: Arc::new(HolderIndex::default()),
+    };
 
-    let state = AppState { api: Arc::new(api) };
+    JobManager::resume(&state);
+    HolderIndex::start(&state);
 
     HttpServer::new(move || {
         let cors = Cors::default()
@@ -43,9 +92,12 @@ async fn main() -> std::io::Result<()> {
             .allowed_methods(vec!["GET", "POST"])
             .allowed_headers(vec![http::header::AUTHORIZATION, http::header::ACCEPT])
             .allowed_header(http::header::CONTENT_TYPE)
+            .allowed_header(idempotency::IDEMPOTENCY_KEY_HEADER)
+            .expose_headers(vec![idempotency::EXTRINSIC_HASH_HEADER])
             .max_age(3600);
 
         App::new()
+            .wrap(idempotency::Idempotency)
             .wrap(middleware::Compress::default())
             .wrap(middleware::Logger::default())
             .wrap(cors)
@@ -58,19 +110,37 @@ async fn main() -> std::io::Result<()> {
             .route("account/fund", web::post().to(account::fund))
             .route("account/set_balance", web::post().to(account::set_balance))
             .route("account/balance", web::post().to(account::balance))
+            .route("keystore/generate", web::post().to(keystore::generate))
+            .route("keystore/import", web::post().to(keystore::import))
+            .route("keystore/list", web::post().to(keystore::list))
+            .route("keystore/remove", web::post().to(keystore::remove))
             .route("asset/create_class", web::post().to(asset::create_class))
             .route("asset/class_info", web::post().to(asset::class_info))
+            .route(
+                "asset/update_class_metadata",
+                web::post().to(asset::update_class_metadata),
+            )
             .route("asset/create", web::post().to(asset::create))
             .route("asset/info", web::post().to(asset::info))
+            .route("asset/classes", web::post().to(asset::classes))
+            .route("asset/list", web::post().to(asset::list))
             .route(
                 "asset/update_metadata",
                 web::post().to(asset::update_metadata),
             )
             .route("asset/mint", web::post().to(asset::mint))
             .route("asset/burn", web::post().to(asset::burn))
+            .route("asset/batch_mint", web::post().to(asset::batch_mint))
+            .route("asset/batch_burn", web::post().to(asset::batch_burn))
             .route("asset/balance", web::post().to(asset::balance))
             .route("asset/balances", web::post().to(asset::balances))
+            .route("asset/holders", web::post().to(asset::holders))
+            .route("asset/supply", web::post().to(asset::supply))
             .route("asset/transfer_from", web::post().to(asset::transfer_from))
+            .route(
+                "asset/batch_transfer_from",
+                web::post().to(asset::batch_transfer_from),
+            )
             .route("bag/register", web::post().to(bag::register))
             .route("bag/create", web::post().to(bag::create))
             .route("bag/sweep", web::post().to(bag::sweep))
@@ -105,6 +175,38 @@ async fn main() -> std::io::Result<()> {
                 "market/exchange_assets",
                 web::post().to(market::exchange_assets),
             )
+            .route(
+                "tx/prepare/{module}/{operation}",
+                web::post().to(tx::prepare),
+            )
+            .route("tx/submit/{module}/{operation}", web::post().to(tx::submit))
+            .route("tx/dynamic", web::post().to(dynamic::tx))
+            .route("tx/status", web::post().to(tracker::status))
+            .route("chain/constants", web::post().to(chain::constants))
+            .route("chain/info", web::post().to(chain::info))
+            .route("storage/{pallet}/{entry}", web::post().to(storage::query))
+            .route("scheduler/schedule", web::post().to(scheduler::schedule))
+            .route(
+                "scheduler/cancel_named",
+                web::post().to(scheduler::cancel_named),
+            )
+            .route("scheduler/list", web::post().to(scheduler::list))
+            .route("council/propose", web::post().to(council::propose))
+            .route("council/vote", web::post().to(council::vote))
+            .route("council/close", web::post().to(council::close))
+            .route("council/disapprove", web::post().to(council::disapprove))
+            .route("council/execute", web::post().to(council::member_execute))
+            .route("council/set_members", web::post().to(council::set_members))
+            .route("council/proposals", web::post().to(council::proposals))
+            .route("sudo/call", web::post().to(sudo::call))
+            .route("job/airdrop", web::post().to(job::airdrop))
+            .route("job/status", web::post().to(job::status))
+            .route("job/retry", web::post().to(job::retry))
+            .route("fees/{module}/{operation}", web::post().to(fees::estimate))
+            .route(
+                "dry_run/{module}/{operation}",
+                web::post().to(dry_run::dry_run),
+            )
     })
     .bind((args.listen.host_str().unwrap(), args.listen.port().unwrap()))?
     .run()
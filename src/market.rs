@@ -1,8 +1,10 @@
 use crate::state::*;
+use crate::tx::*;
 use crate::util::*;
 use actix_web::{error, web, HttpResponse};
 use serde_json::json;
-use subxt::tx::PairSigner;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::PolkadotConfig;
 use sugarfunge_api_types::market::*;
 use sugarfunge_api_types::primitives::*;
 use sugarfunge_api_types::sugarfunge;
@@ -40,34 +42,46 @@ pub async fn create_market(
     data: web::Data<AppState>,
     req: web::Json<CreateMarketInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let api = &data.api;
-
-    let call = sugarfunge::tx()
-        .market()
-        .create_market(req.market_id.into());
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::market::events::Created>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(CreateMarketOutput {
-            who: event.who.into(),
-            market_id: event.market_id.into(),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::market::events::Created"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for CreateMarketInput {
+    type Output = CreateMarketOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call = sugarfunge::tx()
+            .market()
+            .create_market(self.market_id.into());
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CreateMarketOutput> {
+        let result = events
+            .find_first::<sugarfunge::market::events::Created>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(CreateMarketOutput {
+                who: event.who.into(),
+                market_id: event.market_id.into(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::market::events::Created"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -75,40 +89,52 @@ pub async fn create_market_rate(
     data: web::Data<AppState>,
     req: web::Json<CreateMarketRateInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let api = &data.api;
-
-    let rates = &req.rates.rates; //transform_input(&req.rates.rates);
-    let rates = extrinsinc_rates(rates);
-
-    let call = sugarfunge::tx().market().create_market_rate(
-        req.market_id.into(),
-        u64::from(req.market_rate_id),
-        rates,
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::market::events::RateCreated>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(CreateMarketRateOutput {
-            who: event.who.into(),
-            market_id: event.market_id.into(),
-            market_rate_id: MarketId::from(event.market_rate_id),
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::market::events::RateCreated"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for CreateMarketRateInput {
+    type Output = CreateMarketRateOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let rates = &self.rates.rates; //transform_input(&self.rates.rates);
+        let rates = extrinsinc_rates(rates);
+
+        let call = sugarfunge::tx().market().create_market_rate(
+            self.market_id.into(),
+            u64::from(self.market_rate_id),
+            rates,
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CreateMarketRateOutput> {
+        let result = events
+            .find_first::<sugarfunge::market::events::RateCreated>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(CreateMarketRateOutput {
+                who: event.who.into(),
+                market_id: event.market_id.into(),
+                market_rate_id: MarketId::from(event.market_rate_id),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::market::events::RateCreated"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -116,40 +142,52 @@ pub async fn deposit_assets(
     data: web::Data<AppState>,
     req: web::Json<DepositAssetsInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let api = &data.api;
-
-    let call = sugarfunge::tx().market().deposit(
-        req.market_id.into(),
-        u64::from(req.market_rate_id),
-        req.amount.into(),
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::market::events::Deposit>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(DepositAssetsOutput {
-            who: event.who.into(),
-            market_id: event.market_id.into(),
-            market_rate_id: MarketId::from(event.market_rate_id),
-            amount: event.amount.into(),
-            balances: transform_balances(event.balances),
-            success: event.success,
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::market::events::Deposit"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for DepositAssetsInput {
+    type Output = DepositAssetsOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call = sugarfunge::tx().market().deposit(
+            self.market_id.into(),
+            u64::from(self.market_rate_id),
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<DepositAssetsOutput> {
+        let result = events
+            .find_first::<sugarfunge::market::events::Deposit>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(DepositAssetsOutput {
+                who: event.who.into(),
+                market_id: event.market_id.into(),
+                market_rate_id: MarketId::from(event.market_rate_id),
+                amount: event.amount.into(),
+                balances: transform_balances(event.balances),
+                success: event.success,
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::market::events::Deposit"),
+                description: String::new(),
+            })),
+        }
     }
 }
 
@@ -157,39 +195,51 @@ pub async fn exchange_assets(
     data: web::Data<AppState>,
     req: web::Json<ExchangeAssetsInput>,
 ) -> error::Result<HttpResponse> {
-    let pair = get_pair_from_seed(&req.seed)?;
-    let signer = PairSigner::new(pair);
-    let api = &data.api;
-
-    let call = sugarfunge::tx().market().exchange_assets(
-        req.market_id.into(),
-        u64::from(req.market_rate_id),
-        req.amount.into(),
-    );
-
-    let result = api
-        .tx()
-        .sign_and_submit_then_watch(&call, &signer, Default::default())
-        .await
-        .map_err(map_subxt_err)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(map_sf_err)?;
-    let result = result
-        .find_first::<sugarfunge::market::events::Exchanged>()
-        .map_err(map_subxt_err)?;
-    match result {
-        Some(event) => Ok(HttpResponse::Ok().json(ExchangeAssetsOutput {
-            buyer: event.buyer.into(),
-            market_id: event.market_id.into(),
-            market_rate_id: MarketId::from(event.market_rate_id),
-            amount: event.amount.into(),
-            balances: transform_balances(event.balances),
-            success: event.success,
-        })),
-        None => Ok(HttpResponse::BadRequest().json(RequestError {
-            message: json!("Failed to find sugarfunge::market::events::Exchange"),
-            description: String::new(),
-        })),
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for ExchangeAssetsInput {
+    type Output = ExchangeAssetsOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call = sugarfunge::tx().market().exchange_assets(
+            self.market_id.into(),
+            u64::from(self.market_rate_id),
+            self.amount.into(),
+        );
+
+        api.tx().call_data(&call).map_err(map_subxt_err)
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<ExchangeAssetsOutput> {
+        let result = events
+            .find_first::<sugarfunge::market::events::Exchanged>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(ExchangeAssetsOutput {
+                buyer: event.buyer.into(),
+                market_id: event.market_id.into(),
+                market_rate_id: MarketId::from(event.market_rate_id),
+                amount: event.amount.into(),
+                balances: transform_balances(event.balances),
+                success: event.success,
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::market::events::Exchange"),
+                description: String::new(),
+            })),
+        }
     }
 }
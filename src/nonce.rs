@@ -0,0 +1,94 @@
+use crate::state::*;
+use crate::tx::{EncodedCall, TxParams};
+use crate::util::*;
+use actix_web::error;
+use futures::lock::Mutex as AsyncMutex;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::tx::{Signer, TxProgress};
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+pub type Progress = TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+
+/// Next nonce to use of one signer account, `None` until fetched from the
+/// node. Held while a transaction of the account is being submitted.
+type AccountNonce = AsyncMutex<Option<u64>>;
+
+/// Hands out nonces locally per signer account, so that concurrent
+/// requests signed by the same key don't reuse the nonce of one another
+#[derive(Default)]
+pub struct NonceManager {
+    accounts: Mutex<HashMap<AccountId32, Arc<AccountNonce>>>,
+}
+
+impl NonceManager {
+    fn account(&self, account: &AccountId32) -> Arc<AccountNonce> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .entry(account.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// Sign and submit a call with the next nonce of the signer.
+    /// A locally held nonce rejected by the node is refetched once,
+    /// accounting for the transactions already in the pool.
+    pub async fn submit<S: Signer<PolkadotConfig>>(
+        &self,
+        api: &ClientAPI,
+        rpc: &LegacyRpcMethods<PolkadotConfig>,
+        call: &EncodedCall,
+        signer: &S,
+        params: impl Fn() -> TxParams,
+    ) -> error::Result<Progress> {
+        let account_id = signer.account_id();
+        let account = self.account(&account_id);
+        let mut next = account.lock().await;
+
+        let mut retry = next.is_some();
+        loop {
+            let nonce = match *next {
+                Some(nonce) => nonce,
+                None => rpc
+                    .system_account_next_index(&account_id)
+                    .await
+                    .map_err(map_subxt_err)?,
+            };
+
+            let extrinsic = api
+                .tx()
+                .create_signed_with_nonce(call, signer, nonce, params());
+            let result = match extrinsic {
+                Ok(extrinsic) => extrinsic.submit_and_watch().await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(progress) => {
+                    *next = Some(nonce + 1);
+                    return Ok(progress);
+                }
+                Err(e) => {
+                    *next = None;
+                    if !retry {
+                        return Err(map_subxt_err(e));
+                    }
+                    retry = false;
+                }
+            }
+        }
+    }
+
+    /// Forget the local nonce of an account, e.g. after one of its
+    /// transactions was dropped, so the next one refetches it. Waits for
+    /// the submission in progress, which would hand out a stale nonce.
+    pub async fn resync(&self, account: &AccountId32) {
+        let account = self.accounts.lock().unwrap().get(account).cloned();
+        if let Some(account) = account {
+            *account.lock().await = None;
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::dynamic::call_name;
+use crate::state::*;
+use crate::storage::KeyDecoder;
+use crate::sudo::check_sudid;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use codec::Encode;
+use futures::StreamExt;
+use serde_json::json;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::PolkadotConfig;
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::scheduler::*;
+use sugarfunge_api_types::sugarfunge;
+use sugarfunge_api_types::sugarfunge::runtime_types::frame_support::traits::preimages::Bounded;
+use sugarfunge_api_types::sugarfunge::runtime_types::pallet_scheduler::pallet::Call as SchedulerCall;
+use sugarfunge_api_types::sugarfunge::runtime_types::sugarfunge_runtime::RuntimeCall;
+
+/// Between the highest priority, 0, and the lowest, 255
+const DEFAULT_PRIORITY: u8 = 127;
+
+fn invalid_name(name: &str) -> actix_web::Error {
+    map_request_err(RequestError {
+        message: json!(format!("Invalid schedule name {}", name)),
+        description: "Error in scheduler".into(),
+    })
+}
+
+/// Call data of a scheduler call made with the given origin
+fn scheduler_call_data(
+    api: &ClientAPI,
+    call: SchedulerCall,
+    origin: ScheduleOrigin,
+) -> error::Result<Vec<u8>> {
+    let call = RuntimeCall::Scheduler(call);
+    match origin {
+        ScheduleOrigin::Root => {
+            let call = sugarfunge::tx().sudo().sudo(call);
+            api.tx().call_data(&call).map_err(map_subxt_err)
+        }
+        ScheduleOrigin::Signed => Ok(call.encode()),
+    }
+}
+
+/// Schedule an operation for a block, or after a number of blocks
+pub async fn schedule(
+    data: web::Data<AppState>,
+    req: web::Json<ScheduleInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for ScheduleInput {
+    type Output = ScheduleOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call = operation_call(api, &self.module, &self.operation, &self.input)?;
+        // Calls scheduled by root don't need to go through sudo
+        let call = match self.origin.unwrap_or_default() {
+            ScheduleOrigin::Root => Box::new(without_sudo(call)),
+            ScheduleOrigin::Signed => Box::new(call),
+        };
+        let maybe_periodic = self
+            .periodic
+            .map(|periodic| (periodic.period, periodic.count));
+        let priority = self.priority.unwrap_or(DEFAULT_PRIORITY);
+        let id = match &self.name {
+            Some(name) => Some(schedule_id(name).ok_or_else(|| invalid_name(name))?),
+            None => None,
+        };
+
+        let call = match (id, self.at, self.after) {
+            (None, Some(when), _) => SchedulerCall::schedule {
+                when,
+                maybe_periodic,
+                priority,
+                call,
+            },
+            (Some(id), Some(when), _) => SchedulerCall::schedule_named {
+                id,
+                when,
+                maybe_periodic,
+                priority,
+                call,
+            },
+            (None, None, Some(after)) => SchedulerCall::schedule_after {
+                after,
+                maybe_periodic,
+                priority,
+                call,
+            },
+            (Some(id), None, Some(after)) => SchedulerCall::schedule_named_after {
+                id,
+                after,
+                maybe_periodic,
+                priority,
+                call,
+            },
+            (_, None, None) => {
+                return Err(map_request_err(RequestError {
+                    message: json!("One of at or after is required"),
+                    description: "Error in scheduler::schedule".into(),
+                }))
+            }
+        };
+        scheduler_call_data(api, call, self.origin.unwrap_or_default())
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<ScheduleOutput> {
+        check_sudid(events)?;
+        let result = events
+            .find_first::<sugarfunge::scheduler::events::Scheduled>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(ScheduleOutput {
+                when: event.when,
+                index: event.index,
+                name: self.name.clone(),
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::scheduler::events::Scheduled"),
+                description: "Error in scheduler::schedule".into(),
+            })),
+        }
+    }
+}
+
+/// Cancel a named schedule
+pub async fn cancel_named(
+    data: web::Data<AppState>,
+    req: web::Json<CancelNamedInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for CancelNamedInput {
+    type Output = CancelNamedOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let id = schedule_id(&self.name).ok_or_else(|| invalid_name(&self.name))?;
+        let call = SchedulerCall::cancel_named { id };
+        scheduler_call_data(api, call, self.origin.unwrap_or_default())
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<CancelNamedOutput> {
+        check_sudid(events)?;
+        let result = events
+            .find_first::<sugarfunge::scheduler::events::Canceled>()
+            .map_err(map_subxt_err)?;
+        match result {
+            Some(event) => Ok(CancelNamedOutput {
+                when: event.when,
+                index: event.index,
+            }),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::scheduler::events::Canceled"),
+                description: "Error in scheduler::cancel_named".into(),
+            })),
+        }
+    }
+}
+
+/// List the pending schedules of `Scheduler::Agenda`
+pub async fn list(
+    data: web::Data<AppState>,
+    req: Option<web::Json<SchedulesInput>>,
+) -> error::Result<HttpResponse> {
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let api = &data.api;
+    let metadata = api.metadata();
+    let decoder = KeyDecoder::new(&metadata, "Scheduler", "Agenda")?;
+
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let storage = block.storage();
+    let mut agendas = storage
+        .iter(sugarfunge::storage().scheduler().agenda_iter())
+        .await
+        .map_err(map_subxt_err)?;
+
+    let mut schedules = Vec::new();
+    while let Some(agenda) = agendas.next().await {
+        let agenda = agenda.map_err(map_subxt_err)?;
+        // Agendas are keyed by the block number
+        let when: u32 = decoder.keys(&agenda.key_bytes)?.next()?;
+
+        for (index, scheduled) in agenda.value.0.into_iter().enumerate() {
+            let Some(scheduled) = scheduled else {
+                continue;
+            };
+            let name = scheduled.maybe_id.as_ref().map(schedule_name);
+            if req.name.is_some() && name != req.name {
+                continue;
+            }
+            let call = match &scheduled.call {
+                Bounded::Inline(call) => call_name(&metadata, &call.0),
+                _ => None,
+            };
+            schedules.push(ScheduleItemOutput {
+                when,
+                index: index as u32,
+                name,
+                priority: scheduled.priority,
+                periodic: scheduled
+                    .maybe_periodic
+                    // The runs left after the next one are stored
+                    .map(|(period, count)| SchedulePeriod {
+                        period,
+                        count: count + 1,
+                    }),
+                call,
+            });
+        }
+    }
+    Ok(HttpResponse::Ok().json(SchedulesOutput {
+        schedules,
+        block: at_block(&block),
+    }))
+}
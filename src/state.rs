@@ -1,4 +1,11 @@
+use crate::holders::HolderIndex;
+use crate::idempotency::IdempotencyStore;
+use crate::job::JobManager;
+use crate::keystore::Keystore;
+use crate::nonce::NonceManager;
+use crate::tracker::TxTracker;
 use std::sync::Arc;
+use subxt::backend::legacy::LegacyRpcMethods;
 use subxt::{client::OnlineClient, PolkadotConfig};
 
 pub type ClientAPI = Arc<OnlineClient<PolkadotConfig>>;
@@ -6,4 +13,11 @@ pub type ClientAPI = Arc<OnlineClient<PolkadotConfig>>;
 #[derive(Clone)]
 pub struct AppState {
     pub api: ClientAPI,
+    pub rpc: LegacyRpcMethods<PolkadotConfig>,
+    pub keystore: Arc<Keystore>,
+    pub nonces: Arc<NonceManager>,
+    pub tracker: Arc<TxTracker>,
+    pub idempotency: Arc<IdempotencyStore>,
+    pub jobs: Arc<JobManager>,
+    pub holders: Arc<HolderIndex>,
 }
@@ -0,0 +1,356 @@
+use crate::dynamic::*;
+use crate::state::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use codec::Decode;
+use scale_info::{PortableRegistry, TypeDef};
+use serde_json::json;
+use std::collections::HashMap;
+use subxt::ext::scale_value::{self, Value};
+use subxt::metadata::types::{StorageEntryType, StorageHasher};
+use subxt::storage::StorageAddress;
+use subxt::utils::H256;
+use subxt::Metadata;
+use sugarfunge_api_types::storage::*;
+use sugarfunge_api_types::validation::FieldError;
+
+/// Length of the pallet and entry hashes starting every storage key
+const ENTRY_PREFIX_LEN: usize = 32;
+
+const DEFAULT_PAGE_LIMIT: u32 = 100;
+const MAX_PAGE_LIMIT: u32 = 1000;
+
+/// Number of items of a page of a listing
+pub fn page_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+}
+
+fn map_key_err(key: &[u8], reason: impl std::fmt::Display) -> actix_web::Error {
+    map_request_err(RequestError {
+        message: json!(format!("0x{}: {}", hex::encode(key), reason)),
+        description: "Invalid storage key".into(),
+    })
+}
+
+/// Decodes the keys of a storage map from its storage keys, following
+/// the hashers of the entry in the runtime metadata
+pub struct KeyDecoder {
+    hashers: Vec<StorageHasher>,
+}
+
+impl KeyDecoder {
+    pub fn new(metadata: &Metadata, pallet: &str, entry: &str) -> error::Result<Self> {
+        let entry_type = metadata
+            .pallet_by_name(pallet)
+            .and_then(|pallet| pallet.storage())
+            .and_then(|storage| storage.entry_by_name(entry))
+            .map(|entry| entry.entry_type())
+            .ok_or_else(|| {
+                map_dynamic_err(format!(
+                    "entry: unknown storage entry {}::{}",
+                    pallet, entry
+                ))
+            })?;
+        let hashers = match entry_type {
+            StorageEntryType::Plain(_) => Vec::new(),
+            StorageEntryType::Map { hashers, .. } => hashers.clone(),
+        };
+        Ok(KeyDecoder { hashers })
+    }
+
+    /// Keys of a storage key, to be decoded in order
+    pub fn keys<'a>(&'a self, key: &'a [u8]) -> error::Result<StorageKeys<'a>> {
+        Ok(StorageKeys {
+            hashers: self.hashers.iter(),
+            key,
+            rest: key
+                .get(ENTRY_PREFIX_LEN..)
+                .ok_or_else(|| map_key_err(key, "too short"))?,
+        })
+    }
+}
+
+pub struct StorageKeys<'a> {
+    hashers: std::slice::Iter<'a, StorageHasher>,
+    key: &'a [u8],
+    rest: &'a [u8],
+}
+
+impl StorageKeys<'_> {
+    /// Decode the next key, only keys hashed with a concat hasher are kept
+    /// in the storage key
+    pub fn next<K: Decode>(&mut self) -> error::Result<K> {
+        self.skip_hash()?;
+        K::decode(&mut self.rest).map_err(|e| map_key_err(self.key, e))
+    }
+
+    /// Decode the next key as a value of the given type
+    pub fn next_value(
+        &mut self,
+        type_id: u32,
+        types: &PortableRegistry,
+    ) -> error::Result<Value<u32>> {
+        self.skip_hash()?;
+        scale_value::scale::decode_as_type(&mut self.rest, type_id, types)
+            .map_err(|e| map_key_err(self.key, e))
+    }
+
+    fn skip_hash(&mut self) -> error::Result<()> {
+        let hash_len = match self.hashers.next() {
+            Some(StorageHasher::Blake2_128Concat) => 16,
+            Some(StorageHasher::Twox64Concat) => 8,
+            Some(StorageHasher::Identity) => 0,
+            Some(hasher) => {
+                return Err(map_key_err(
+                    self.key,
+                    format!("a key hashed with {:?} can't be decoded", hasher),
+                ))
+            }
+            None => return Err(map_key_err(self.key, "no more keys")),
+        };
+        self.rest = self
+            .rest
+            .get(hash_len..)
+            .ok_or_else(|| map_key_err(self.key, "too short"))?;
+        Ok(())
+    }
+}
+
+/// Storage keys under a prefix at a block, fetched a page at a time
+/// from the key a previous listing ended at
+pub struct KeyPages<'a> {
+    data: &'a AppState,
+    prefix: Vec<u8>,
+    block_hash: H256,
+    page_size: u32,
+    start: Option<Vec<u8>>,
+    page: std::vec::IntoIter<Vec<u8>>,
+    done: bool,
+}
+
+impl<'a> KeyPages<'a> {
+    /// `cursor` is a key returned by [`key_cursor`], it must be under `prefix`
+    pub fn new(
+        data: &'a AppState,
+        prefix: Vec<u8>,
+        block_hash: H256,
+        cursor: Option<&str>,
+        page_size: u32,
+    ) -> error::Result<Self> {
+        let start = match cursor {
+            Some(cursor) => {
+                let key = hex::decode(cursor.trim_start_matches("0x"))
+                    .ok()
+                    .filter(|key| key.starts_with(&prefix))
+                    .ok_or_else(|| {
+                        map_validation_err(vec![FieldError {
+                            field: "cursor".into(),
+                            reason: "not a cursor of this listing".into(),
+                        }])
+                    })?;
+                Some(key)
+            }
+            None => None,
+        };
+        Ok(KeyPages {
+            data,
+            prefix,
+            block_hash,
+            page_size: page_size.max(1),
+            start,
+            page: Vec::new().into_iter(),
+            done: false,
+        })
+    }
+
+    pub async fn next(&mut self) -> error::Result<Option<Vec<u8>>> {
+        if let Some(key) = self.page.next() {
+            return Ok(Some(key));
+        }
+        if self.done {
+            return Ok(None);
+        }
+        let keys = self
+            .data
+            .rpc
+            .state_get_keys_paged(
+                &self.prefix,
+                self.page_size,
+                self.start.as_deref(),
+                Some(self.block_hash),
+            )
+            .await
+            .map_err(map_subxt_err)?;
+        self.done = keys.len() < self.page_size as usize;
+        self.start = keys.last().cloned();
+        self.page = keys.into_iter();
+        Ok(self.page.next())
+    }
+}
+
+/// Values of the storage keys of an entry at a block, read in one request,
+/// `address` is any address of the entry and gives the type of its values
+pub async fn fetch_values<A: StorageAddress>(
+    data: &AppState,
+    block_hash: H256,
+    _address: &A,
+    keys: &[Vec<u8>],
+) -> error::Result<Vec<Option<A::Target>>>
+where
+    A::Target: Decode,
+{
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let change_sets = data
+        .rpc
+        .state_query_storage_at(keys.iter().map(Vec::as_slice), Some(block_hash))
+        .await
+        .map_err(map_subxt_err)?;
+    let mut values: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    for (key, value) in change_sets.into_iter().flat_map(|set| set.changes) {
+        if let Some(value) = value {
+            values.insert(key.0, value.0);
+        }
+    }
+    keys.iter()
+        .map(|key| {
+            values
+                .get(key)
+                .map(|value| A::Target::decode(&mut value.as_slice()))
+                .transpose()
+                .map_err(|e| map_key_err(key, e))
+        })
+        .collect()
+}
+
+/// Cursor resuming a listing after the given key
+pub fn key_cursor(key: &[u8]) -> String {
+    format!("0x{}", hex::encode(key))
+}
+
+/// Type of each key of a storage map, hashed one after the other
+fn key_types(key_ty: u32, hashers: usize, types: &PortableRegistry) -> Vec<u32> {
+    if hashers == 1 {
+        return vec![key_ty];
+    }
+    match types.resolve(key_ty).map(|ty| &ty.type_def) {
+        Some(TypeDef::Tuple(tuple)) => tuple.fields.iter().map(|field| field.id).collect(),
+        _ => vec![key_ty],
+    }
+}
+
+/// Read a storage entry of any pallet, keys and values are converted
+/// with the runtime metadata. A map given only some of its keys is
+/// iterated over the rest, a page at a time.
+pub async fn query(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    req: Option<web::Json<StorageInput>>,
+) -> error::Result<HttpResponse> {
+    let (pallet, entry) = path.into_inner();
+    let req = req.map(|req| req.into_inner()).unwrap_or_default();
+    let api = &data.api;
+    let metadata = api.metadata();
+    let types = metadata.types();
+
+    let entry_type = metadata
+        .pallet_by_name(&pallet)
+        .and_then(|pallet| pallet.storage())
+        .and_then(|storage| storage.entry_by_name(&entry))
+        .map(|entry| entry.entry_type())
+        .ok_or_else(|| {
+            map_dynamic_err(format!(
+                "entry: unknown storage entry {}::{}",
+                pallet, entry
+            ))
+        })?;
+    let (key_types, value_ty) = match entry_type {
+        StorageEntryType::Plain(value_ty) => (Vec::new(), *value_ty),
+        StorageEntryType::Map {
+            hashers,
+            key_ty,
+            value_ty,
+        } => (key_types(*key_ty, hashers.len(), types), *value_ty),
+    };
+    if req.keys.len() > key_types.len() {
+        return Err(map_dynamic_err(format!(
+            "keys: expected at most {} keys",
+            key_types.len()
+        )));
+    }
+    let keys = req
+        .keys
+        .iter()
+        .zip(&key_types)
+        .enumerate()
+        .map(|(index, (key, key_ty))| {
+            json_to_value(key, *key_ty, types, &format!("keys[{}]", index))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(map_dynamic_err)?;
+
+    let block = block_at(&data, req.at.as_ref()).await?;
+    let storage = block.storage();
+    let address = subxt::dynamic::storage(pallet.as_str(), entry.as_str(), keys);
+
+    if req.keys.len() == key_types.len() {
+        let value = match storage.fetch(&address).await.map_err(map_subxt_err)? {
+            Some(value) => value_to_json(&value.to_value().map_err(map_subxt_err)?, types),
+            None => serde_json::Value::Null,
+        };
+        return Ok(HttpResponse::Ok().json(StorageOutput {
+            block: at_block(&block),
+            value: Some(value),
+            entries: None,
+            next_key: None,
+        }));
+    }
+
+    let limit = page_limit(req.limit);
+    let prefix = api
+        .storage()
+        .address_bytes(&address)
+        .map_err(map_subxt_err)?;
+    let mut keys = KeyPages::new(&data, prefix, block.hash(), req.start_key.as_deref(), limit)?;
+    let decoder = KeyDecoder::new(&metadata, &pallet, &entry)?;
+
+    let mut entries = Vec::new();
+    let mut next_key = None;
+    while let Some(key) = keys.next().await? {
+        let Some(bytes) = storage
+            .fetch_raw(key.clone())
+            .await
+            .map_err(map_subxt_err)?
+        else {
+            continue;
+        };
+        let value = scale_value::scale::decode_as_type(&mut bytes.as_slice(), value_ty, types)
+            .map_err(|e| map_key_err(&key, e))?;
+        // Keys hashed without their value, and those after them, are null
+        let mut key_values = decoder.keys(&key)?;
+        let mut decoded_keys = Vec::new();
+        for key_ty in &key_types {
+            match key_values.next_value(*key_ty, types) {
+                Ok(value) => decoded_keys.push(value_to_json(&value, types)),
+                Err(_) => break,
+            }
+        }
+        decoded_keys.resize(key_types.len(), serde_json::Value::Null);
+        entries.push(StorageEntryOutput {
+            key: key_cursor(&key),
+            keys: decoded_keys,
+            value: value_to_json(&value, types),
+        });
+        if entries.len() == limit as usize {
+            next_key = Some(key_cursor(&key));
+            break;
+        }
+    }
+    Ok(HttpResponse::Ok().json(StorageOutput {
+        block: at_block(&block),
+        value: None,
+        entries: Some(entries),
+        next_key,
+    }))
+}
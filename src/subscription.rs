@@ -6,6 +6,7 @@ use crossbeam::channel;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use sugarfunge_api_types::sugarfunge;
+use sugarfunge_api_types::tx::*;
 
 /// How often heartbeat pings are sent
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
@@ -224,7 +225,18 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for SubcriptionServic
             Ok(ws::Message::Pong(_)) => {
                 self.last_client_heartbeat = Instant::now();
             }
-            Ok(ws::Message::Text(text)) => ctx.text(format!("echo: {}", text)),
+            Ok(ws::Message::Text(text)) => {
+                // Follow the status of a tracked extrinsic
+                let watch = serde_json::from_str::<TxStatusInput>(&text)
+                    .ok()
+                    .and_then(|req| self.data.tracker.watch(&req.tracking_id));
+                match watch {
+                    Some(statuses) => {
+                        ctx.add_stream(statuses);
+                    }
+                    None => ctx.text(format!("echo: {}", text)),
+                }
+            }
             Ok(ws::Message::Binary(bin)) => ctx.binary(bin),
             Ok(ws::Message::Close(reason)) => {
                 ctx.close(reason);
@@ -235,6 +247,16 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for SubcriptionServic
     }
 }
 
+/// Handler for the status updates of tracked extrinsics
+impl StreamHandler<TxStatusOutput> for SubcriptionServiceWS {
+    fn handle(&mut self, status: TxStatusOutput, ctx: &mut Self::Context) {
+        ctx.text(serde_json::to_string(&status).unwrap());
+    }
+
+    /// Keep the connection open once the extrinsic is done
+    fn finished(&mut self, _ctx: &mut Self::Context) {}
+}
+
 /// WebSocket handshake and start `SubcriptionServiceWS` actor.
 pub async fn ws(
     data: web::Data<AppState>,
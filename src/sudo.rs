@@ -0,0 +1,129 @@
+use crate::dynamic::*;
+use crate::state::*;
+use crate::tx::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use codec::Decode;
+use serde_json::json;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::PolkadotConfig;
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::sudo::*;
+use sugarfunge_api_types::sugarfunge;
+use sugarfunge_api_types::sugarfunge::runtime_types::sp_runtime::DispatchError as RuntimeDispatchError;
+use sugarfunge_api_types::sugarfunge::runtime_types::sp_weights::weight_v2::Weight;
+use sugarfunge_api_types::sugarfunge::runtime_types::sugarfunge_runtime::RuntimeCall;
+
+/// Outcome of the call dispatched by sudo
+fn sudo_output(sudo_result: &Result<(), RuntimeDispatchError>) -> SudoCallOutput {
+    match sudo_result {
+        Ok(()) => SudoCallOutput {
+            success: true,
+            error: None,
+        },
+        Err(e) => SudoCallOutput {
+            success: false,
+            error: Some(runtime_dispatch_error_value(e)),
+        },
+    }
+}
+
+/// Fails with the error of the call dispatched by sudo, the sudo
+/// extrinsic itself succeeds even when that call failed
+pub fn check_sudid(events: &ExtrinsicEvents<PolkadotConfig>) -> error::Result<()> {
+    let sudid = events
+        .find_first::<sugarfunge::sudo::events::Sudid>()
+        .map_err(map_subxt_err)?;
+    match sudid.map(|event| event.sudo_result) {
+        Some(Err(e)) => Err(map_request_err(RequestError {
+            message: runtime_dispatch_error_value(&e),
+            description: "Sudo call failed".into(),
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// Dispatch any call with the sudo key, arguments are encoded
+/// against the runtime metadata
+pub async fn call(
+    data: web::Data<AppState>,
+    req: web::Json<SudoCallInput>,
+) -> error::Result<HttpResponse> {
+    execute(&data, req.into_inner()).await
+}
+
+impl Operation for SudoCallInput {
+    type Output = SudoCallOutput;
+
+    fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    fn tx_options(&self) -> Option<&TxOptions> {
+        self.tx_options.as_ref()
+    }
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>> {
+        let call_data = dynamic_call_data(api, &self.pallet, &self.call, &self.args)?;
+        let call = RuntimeCall::decode(&mut &call_data[..]).map_err(|e| {
+            map_request_err(RequestError {
+                message: json!(format!("{:?}", e)),
+                description: "Error in sudo::call".into(),
+            })
+        })?;
+
+        let missing = |field: &str| {
+            map_dynamic_err(format!(
+                "{}: required with mode {:?}",
+                field,
+                self.mode.unwrap_or_default()
+            ))
+        };
+        match self.mode.unwrap_or_default() {
+            SudoMode::Sudo => {
+                let call = sugarfunge::tx().sudo().sudo(call);
+                api.tx().call_data(&call).map_err(map_subxt_err)
+            }
+            SudoMode::SudoAs => {
+                let who = self.who.as_ref().ok_or_else(|| missing("who"))?;
+                let who = subxt::utils::AccountId32::try_from(who).map_err(map_account_err)?;
+                let who = subxt::utils::MultiAddress::Id(who);
+                let call = sugarfunge::tx().sudo().sudo_as(who, call);
+                api.tx().call_data(&call).map_err(map_subxt_err)
+            }
+            SudoMode::SudoUncheckedWeight => {
+                let weight = self.weight.ok_or_else(|| missing("weight"))?;
+                let weight = Weight {
+                    ref_time: weight.ref_time,
+                    proof_size: weight.proof_size,
+                };
+                let call = sugarfunge::tx().sudo().sudo_unchecked_weight(call, weight);
+                api.tx().call_data(&call).map_err(map_subxt_err)
+            }
+        }
+    }
+
+    async fn output(
+        &self,
+        _data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<SudoCallOutput> {
+        let sudo_result = match self.mode.unwrap_or_default() {
+            SudoMode::SudoAs => events
+                .find_first::<sugarfunge::sudo::events::SudoAsDone>()
+                .map_err(map_subxt_err)?
+                .map(|event| event.sudo_result),
+            _ => events
+                .find_first::<sugarfunge::sudo::events::Sudid>()
+                .map_err(map_subxt_err)?
+                .map(|event| event.sudo_result),
+        };
+        match sudo_result {
+            Some(sudo_result) => Ok(sudo_output(&sudo_result)),
+            None => Err(map_request_err(RequestError {
+                message: json!("Failed to find sugarfunge::sudo::events::Sudid"),
+                description: "Error in sudo::call".into(),
+            })),
+        }
+    }
+}
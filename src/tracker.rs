@@ -0,0 +1,115 @@
+use crate::state::*;
+use crate::util::*;
+use actix_web::{error, web, HttpResponse};
+use futures::channel::mpsc;
+use rand::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sugarfunge_api_types::tx::*;
+
+/// How long the status of a finished extrinsic remains available
+const TRACKING_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+struct Tracked {
+    status: TxStatusOutput,
+    finished: Option<Instant>,
+    watchers: Vec<mpsc::UnboundedSender<TxStatusOutput>>,
+}
+
+/// Status of the extrinsics submitted without waiting for a block
+#[derive(Default)]
+pub struct TxTracker {
+    tracked: Mutex<HashMap<String, Tracked>>,
+}
+
+fn is_finished(status: ExtrinsicStatus) -> bool {
+    matches!(
+        status,
+        ExtrinsicStatus::Finalized
+            | ExtrinsicStatus::Dropped
+            | ExtrinsicStatus::Invalid
+            | ExtrinsicStatus::Unknown
+    )
+}
+
+impl TxTracker {
+    /// Start tracking an extrinsic, returns its tracking id
+    pub fn track(&self, extrinsic_hash: String) -> String {
+        let tracking_id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+        let mut tracked = self.tracked.lock().unwrap();
+        tracked.retain(|_, tracked| {
+            tracked
+                .finished
+                .map_or(true, |finished| finished.elapsed() < TRACKING_RETENTION)
+        });
+        tracked.insert(
+            tracking_id.clone(),
+            Tracked {
+                status: TxStatusOutput {
+                    tracking_id: tracking_id.clone(),
+                    extrinsic_hash,
+                    status: ExtrinsicStatus::Ready,
+                    block_hash: None,
+                    output: None,
+                    error: None,
+                },
+                finished: None,
+                watchers: Vec::new(),
+            },
+        );
+        tracking_id
+    }
+
+    /// Update the status of an extrinsic and notify its watchers
+    pub fn update(&self, tracking_id: &str, update: impl FnOnce(&mut TxStatusOutput)) {
+        let mut tracked = self.tracked.lock().unwrap();
+        let Some(tracked) = tracked.get_mut(tracking_id) else {
+            return;
+        };
+        update(&mut tracked.status);
+        let status = tracked.status.clone();
+        tracked
+            .watchers
+            .retain(|watcher| watcher.unbounded_send(status.clone()).is_ok());
+        if is_finished(status.status) {
+            tracked.finished = Some(Instant::now());
+            tracked.watchers.clear();
+        }
+    }
+
+    pub fn status(&self, tracking_id: &str) -> Option<TxStatusOutput> {
+        let tracked = self.tracked.lock().unwrap();
+        tracked
+            .get(tracking_id)
+            .map(|tracked| tracked.status.clone())
+    }
+
+    /// Stream of the status updates of an extrinsic, starting with the
+    /// current one and ending once it is finalized or left the pool
+    pub fn watch(&self, tracking_id: &str) -> Option<mpsc::UnboundedReceiver<TxStatusOutput>> {
+        let mut tracked = self.tracked.lock().unwrap();
+        let tracked = tracked.get_mut(tracking_id)?;
+        let (tx, rx) = mpsc::unbounded();
+        tx.unbounded_send(tracked.status.clone()).ok()?;
+        if tracked.finished.is_none() {
+            tracked.watchers.push(tx);
+        }
+        Some(rx)
+    }
+}
+
+/// Get the status of an extrinsic submitted with the `submitted` wait mode
+pub async fn status(
+    data: web::Data<AppState>,
+    req: web::Json<TxStatusInput>,
+) -> error::Result<HttpResponse> {
+    match data.tracker.status(&req.tracking_id) {
+        Some(status) => Ok(HttpResponse::Ok().json(status)),
+        None => Err(map_request_err(RequestError {
+            message: json!("Unknown tracking id"),
+            description: "API error".into(),
+        })),
+    }
+}
@@ -0,0 +1,642 @@
+use crate::idempotency::{self, EXTRINSIC_HASH_HEADER};
+use crate::nonce::Progress;
+use crate::state::*;
+use crate::util::*;
+use crate::validation::validate;
+use actix_web::{error, web, HttpResponse};
+use codec::Decode;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::config::polkadot::PolkadotExtrinsicParamsBuilder;
+use subxt::config::{Config, ExtrinsicParams};
+use subxt::error::DispatchError;
+use subxt::tx::{Signer as _, TxInBlock, TxPayload, TxStatus};
+use subxt::utils::{AccountId32, MultiSignature, H256};
+use subxt::{Metadata, OnlineClient, PolkadotConfig};
+use sugarfunge_api_types::primitives::*;
+use sugarfunge_api_types::sugarfunge::runtime_types::pallet_sudo::pallet::Call as SudoCall;
+use sugarfunge_api_types::sugarfunge::runtime_types::sugarfunge_runtime::RuntimeCall;
+use sugarfunge_api_types::tx::*;
+use sugarfunge_api_types::validation::{FieldError, Validate};
+
+pub type TxParams =
+    <<PolkadotConfig as Config>::ExtrinsicParams as ExtrinsicParams<PolkadotConfig>>::OtherParams;
+
+/// Call data encoded ahead of signing
+pub struct EncodedCall(pub Vec<u8>);
+
+impl TxPayload for EncodedCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+/// Extrinsic exposed by the API, built from its input type and
+/// reporting its result from the events it emitted
+pub trait Operation: DeserializeOwned + Validate + 'static {
+    type Output: Serialize;
+
+    fn signer(&self) -> &Signer;
+
+    fn tx_options(&self) -> Option<&TxOptions>;
+
+    fn call_data(&self, api: &ClientAPI) -> error::Result<Vec<u8>>;
+
+    /// Output of the operation decoded from its events, with the state and
+    /// runtime metadata of the API at hand
+    async fn output(
+        &self,
+        data: &AppState,
+        events: &ExtrinsicEvents<PolkadotConfig>,
+    ) -> error::Result<Self::Output>;
+}
+
+/// Validate the input of an operation, before anything is signed, and encode its call
+pub fn encode_call<O: Operation>(api: &ClientAPI, op: &O) -> error::Result<EncodedCall> {
+    validate(api, op)?;
+    Ok(EncodedCall(op.call_data(api)?))
+}
+
+/// Evaluates `$body` with `$ty` naming the input type of the operation
+/// served at `$module/$operation`, or awaits `$func::<Input>($args)`
+macro_rules! with_operation {
+    ($module:expr, $operation:expr, |$ty:ident| $body:expr) => {
+        match ($module, $operation) {
+            ("account", "fund") => {
+                type $ty = sugarfunge_api_types::account::FundAccountInput;
+                $body
+            }
+            ("account", "set_balance") => {
+                type $ty = sugarfunge_api_types::account::SetBalanceInput;
+                $body
+            }
+            ("asset", "create_class") => {
+                type $ty = sugarfunge_api_types::asset::CreateClassInput;
+                $body
+            }
+            ("asset", "update_class_metadata") => {
+                type $ty = sugarfunge_api_types::asset::UpdateClassMetadataInput;
+                $body
+            }
+            ("asset", "create") => {
+                type $ty = sugarfunge_api_types::asset::CreateInput;
+                $body
+            }
+            ("asset", "update_metadata") => {
+                type $ty = sugarfunge_api_types::asset::UpdateMetadataInput;
+                $body
+            }
+            ("asset", "mint") => {
+                type $ty = sugarfunge_api_types::asset::MintInput;
+                $body
+            }
+            ("asset", "burn") => {
+                type $ty = sugarfunge_api_types::asset::BurnInput;
+                $body
+            }
+            ("asset", "batch_mint") => {
+                type $ty = sugarfunge_api_types::asset::BatchMintInput;
+                $body
+            }
+            ("asset", "batch_burn") => {
+                type $ty = sugarfunge_api_types::asset::BatchBurnInput;
+                $body
+            }
+            ("asset", "transfer_from") => {
+                type $ty = sugarfunge_api_types::asset::TransferFromInput;
+                $body
+            }
+            ("asset", "batch_transfer_from") => {
+                type $ty = sugarfunge_api_types::asset::BatchTransferFromInput;
+                $body
+            }
+            ("bag", "register") => {
+                type $ty = sugarfunge_api_types::bag::RegisterInput;
+                $body
+            }
+            ("bag", "create") => {
+                type $ty = sugarfunge_api_types::bag::CreateInput;
+                $body
+            }
+            ("bag", "sweep") => {
+                type $ty = sugarfunge_api_types::bag::SweepInput;
+                $body
+            }
+            ("bag", "deposit") => {
+                type $ty = sugarfunge_api_types::bag::DepositInput;
+                $body
+            }
+            ("bundle", "register") => {
+                type $ty = sugarfunge_api_types::bundle::RegisterBundleInput;
+                $body
+            }
+            ("bundle", "mint") => {
+                type $ty = sugarfunge_api_types::bundle::MintBundleInput;
+                $body
+            }
+            ("bundle", "burn") => {
+                type $ty = sugarfunge_api_types::bundle::BurnBundleInput;
+                $body
+            }
+            ("validator", "add_validator") => {
+                type $ty = sugarfunge_api_types::validator::AddValidatorInput;
+                $body
+            }
+            ("validator", "remove_validator") => {
+                type $ty = sugarfunge_api_types::validator::RemoveValidatorInput;
+                $body
+            }
+            ("validator", "set_keys") => {
+                type $ty = sugarfunge_api_types::validator::SetKeysInput;
+                $body
+            }
+            ("market", "create_market") => {
+                type $ty = sugarfunge_api_types::market::CreateMarketInput;
+                $body
+            }
+            ("market", "create_market_rate") => {
+                type $ty = sugarfunge_api_types::market::CreateMarketRateInput;
+                $body
+            }
+            ("market", "deposit_assets") => {
+                type $ty = sugarfunge_api_types::market::DepositAssetsInput;
+                $body
+            }
+            ("market", "exchange_assets") => {
+                type $ty = sugarfunge_api_types::market::ExchangeAssetsInput;
+                $body
+            }
+            ("scheduler", "schedule") => {
+                type $ty = sugarfunge_api_types::scheduler::ScheduleInput;
+                $body
+            }
+            ("scheduler", "cancel_named") => {
+                type $ty = sugarfunge_api_types::scheduler::CancelNamedInput;
+                $body
+            }
+            ("council", "propose") => {
+                type $ty = sugarfunge_api_types::council::ProposeInput;
+                $body
+            }
+            ("council", "vote") => {
+                type $ty = sugarfunge_api_types::council::VoteInput;
+                $body
+            }
+            ("council", "close") => {
+                type $ty = sugarfunge_api_types::council::CloseInput;
+                $body
+            }
+            ("council", "disapprove") => {
+                type $ty = sugarfunge_api_types::council::DisapproveInput;
+                $body
+            }
+            ("council", "execute") => {
+                type $ty = sugarfunge_api_types::council::ExecuteInput;
+                $body
+            }
+            ("council", "set_members") => {
+                type $ty = sugarfunge_api_types::council::SetMembersInput;
+                $body
+            }
+            ("tx", "dynamic") => {
+                type $ty = sugarfunge_api_types::dynamic::DynamicTxInput;
+                $body
+            }
+            ("sudo", "call") => {
+                type $ty = sugarfunge_api_types::sudo::SudoCallInput;
+                $body
+            }
+            (module, operation) => Err(map_request_err(RequestError {
+                message: json!(format!("Unknown operation {}/{}", module, operation)),
+                description: "API error".into(),
+            })),
+        }
+    };
+    ($module:expr, $operation:expr, $func:ident($($args:expr),*)) => {
+        $crate::tx::with_operation!($module, $operation, |O| $func::<O>($($args),*).await)
+    };
+}
+
+pub(crate) use with_operation;
+
+/// Call of an operation given by its module, name and input, to be
+/// dispatched by another pallet. Its signer is not used.
+pub fn operation_call(
+    api: &ClientAPI,
+    module: &str,
+    operation: &str,
+    input: &serde_json::Value,
+) -> error::Result<RuntimeCall> {
+    let call = with_operation!(module, operation, |O| {
+        parse_operation::<O>(input.clone()).and_then(|op| encode_call(api, &op))
+    })?;
+    RuntimeCall::decode(&mut &call.0[..]).map_err(|e| {
+        map_request_err(RequestError {
+            message: json!(format!("{:?}", e)),
+            description: "Invalid call".into(),
+        })
+    })
+}
+
+/// Call wrapped by `Sudo::sudo`, for pallets dispatching it with an origin of their own
+pub fn without_sudo(call: RuntimeCall) -> RuntimeCall {
+    match call {
+        RuntimeCall::Sudo(SudoCall::sudo { call }) => *call,
+        call => call,
+    }
+}
+
+/// Sign with the requested signer, submit and wait as set by the tx options
+pub async fn execute<O: Operation>(data: &AppState, op: O) -> error::Result<HttpResponse> {
+    let (progress, account) = sign_and_submit(data, &op).await?;
+    wait_for(data, op, progress, account).await
+}
+
+/// Sign with the requested signer and submit with the tx options,
+/// returns the progress of the extrinsic and the account that signed it
+pub async fn sign_and_submit<O: Operation>(
+    data: &AppState,
+    op: &O,
+) -> error::Result<(Progress, AccountId32)> {
+    let options = op.tx_options().cloned().unwrap_or_default();
+    let signer = get_signer(data, op.signer())?;
+    let call = encode_call(&data.api, op)?;
+    let api = &data.api;
+
+    let mortality = options_mortality(api, &options).await?;
+    let tip = options.tip.map(u128::from).unwrap_or_default();
+    let params = || build_params(mortality, tip);
+
+    let progress = match options.nonce {
+        Some(nonce) => {
+            let progress = api
+                .tx()
+                .create_signed_with_nonce(&call, &signer, nonce, params())
+                .map_err(map_subxt_err)?
+                .submit_and_watch()
+                .await
+                .map_err(map_subxt_err)?;
+            // The pinned nonce may be the one held locally
+            data.nonces.resync(&signer.account_id()).await;
+            progress
+        }
+        None => {
+            data.nonces
+                .submit(api, &data.rpc, &call, &signer, params)
+                .await?
+        }
+    };
+    idempotency::submitted(&format!("{:?}", progress.extrinsic_hash()));
+    Ok((progress, signer.account_id()))
+}
+
+/// Wait for the extrinsic as set by the tx options and report the output
+/// of the operation, or its tracking id if not waiting for a block
+async fn wait_for<O: Operation>(
+    data: &AppState,
+    op: O,
+    progress: Progress,
+    account: AccountId32,
+) -> error::Result<HttpResponse> {
+    let wait = op
+        .tx_options()
+        .and_then(|options| options.wait)
+        .unwrap_or_default();
+    let events = match wait {
+        WaitMode::Submitted => {
+            let extrinsic_hash = format!("{:?}", progress.extrinsic_hash());
+            let tracking_id = data.tracker.track(extrinsic_hash.clone());
+            actix_web::rt::spawn(track(
+                data.clone(),
+                op,
+                progress,
+                account,
+                tracking_id.clone(),
+            ));
+            return Ok(HttpResponse::Ok()
+                .insert_header((EXTRINSIC_HASH_HEADER, extrinsic_hash.clone()))
+                .json(SubmittedExtrinsicOutput {
+                    tracking_id,
+                    extrinsic_hash,
+                }));
+        }
+        WaitMode::InBlock | WaitMode::Finalized => {
+            wait_for_events(data, progress, wait, &account).await?
+        }
+    };
+    Ok(HttpResponse::Ok()
+        .insert_header((
+            EXTRINSIC_HASH_HEADER,
+            format!("{:?}", events.extrinsic_hash()),
+        ))
+        .json(op.output(data, &events).await?))
+}
+
+/// Wait for the extrinsic to be in a block, or finalized, and succeed
+pub async fn wait_for_events(
+    data: &AppState,
+    progress: Progress,
+    wait: WaitMode,
+    account: &AccountId32,
+) -> error::Result<ExtrinsicEvents<PolkadotConfig>> {
+    let events = match wait {
+        WaitMode::Submitted | WaitMode::InBlock => match progress.wait_for_in_block().await {
+            Ok(in_block) => in_block.wait_for_success().await,
+            Err(e) => Err(e),
+        },
+        WaitMode::Finalized => progress.wait_for_finalized_success().await,
+    };
+    match events {
+        Ok(events) => Ok(events),
+        Err(e @ subxt::Error::Runtime(DispatchError::Module(_))) => Err(map_sf_err(e)),
+        Err(e @ subxt::Error::Runtime(_)) => Err(map_subxt_err(e)),
+        Err(e) => {
+            // Anything but a failed dispatch may leave a gap in the nonces
+            data.nonces.resync(account).await;
+            Err(map_subxt_err(e))
+        }
+    }
+}
+
+pub fn error_value(e: error::Error) -> serde_json::Value {
+    let e = e.to_string();
+    serde_json::from_str(&e).unwrap_or_else(|_| json!(e))
+}
+
+/// Record the status of an extrinsic in a block with the decoded output of
+/// the operation, or the error it failed with
+async fn track_in_block<O: Operation>(
+    data: &AppState,
+    op: &O,
+    in_block: TxInBlock<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    tracking_id: &str,
+    status: ExtrinsicStatus,
+) {
+    let block_hash = format!("{:?}", in_block.block_hash());
+    let result = match in_block.wait_for_success().await {
+        Ok(events) => op.output(data, &events).await.and_then(|output| {
+            serde_json::to_value(output).map_err(error::ErrorInternalServerError)
+        }),
+        Err(e) => Err(map_sf_err(e)),
+    };
+    data.tracker.update(tracking_id, |tracked| {
+        tracked.status = status;
+        tracked.block_hash = Some(block_hash);
+        match result {
+            Ok(output) => {
+                tracked.output = Some(output);
+                tracked.error = None;
+            }
+            Err(e) => {
+                tracked.output = None;
+                tracked.error = Some(error_value(e));
+            }
+        }
+    });
+}
+
+/// Follow a submitted extrinsic until it is finalized or leaves the pool
+async fn track<O: Operation>(
+    data: AppState,
+    op: O,
+    mut progress: Progress,
+    account: AccountId32,
+    tracking_id: String,
+) {
+    while let Some(status) = progress.next().await {
+        let (status, message) = match status {
+            Ok(
+                TxStatus::Validated | TxStatus::Broadcasted { .. } | TxStatus::NoLongerInBestBlock,
+            ) => {
+                data.tracker.update(&tracking_id, |tracked| {
+                    tracked.status = ExtrinsicStatus::Ready;
+                    tracked.block_hash = None;
+                    tracked.output = None;
+                    tracked.error = None;
+                });
+                continue;
+            }
+            Ok(TxStatus::InBestBlock(in_block)) => {
+                track_in_block(&data, &op, in_block, &tracking_id, ExtrinsicStatus::InBlock).await;
+                continue;
+            }
+            Ok(TxStatus::InFinalizedBlock(in_block)) => {
+                track_in_block(
+                    &data,
+                    &op,
+                    in_block,
+                    &tracking_id,
+                    ExtrinsicStatus::Finalized,
+                )
+                .await;
+                return;
+            }
+            Ok(TxStatus::Dropped { message }) => (ExtrinsicStatus::Dropped, message),
+            Ok(TxStatus::Invalid { message } | TxStatus::Error { message }) => {
+                (ExtrinsicStatus::Invalid, message)
+            }
+            Err(e) => (ExtrinsicStatus::Invalid, e.to_string()),
+        };
+        data.nonces.resync(&account).await;
+        data.tracker.update(&tracking_id, |tracked| {
+            tracked.status = status;
+            tracked.error = Some(json!(message));
+        });
+        return;
+    }
+    // The subscription ended without a final status, the extrinsic may
+    // still be included
+    data.nonces.resync(&account).await;
+    data.tracker.update(&tracking_id, |tracked| {
+        tracked.status = ExtrinsicStatus::Unknown;
+        tracked.error = Some(json!(
+            "The node stopped reporting the status of the extrinsic, check its extrinsic hash"
+        ));
+    });
+}
+
+pub fn parse_operation<O: Operation>(input: serde_json::Value) -> error::Result<O> {
+    serde_json::from_value(input).map_err(|e| {
+        map_request_err(RequestError {
+            message: json!(e.to_string()),
+            description: "Invalid operation input".into(),
+        })
+    })
+}
+
+/// Account of an externally held key, given as `signer_account`
+pub fn external_signer(signer: &Signer) -> error::Result<AccountId32> {
+    match &signer.signer_account {
+        Some(account) => AccountId32::try_from(account).map_err(map_account_err),
+        None => Err(map_request_err(RequestError {
+            message: json!("signer_account is required to sign externally"),
+            description: "API error".into(),
+        })),
+    }
+}
+
+/// Period, block number and block hash of a mortal extrinsic
+pub type Mortality = Option<(u64, u64, H256)>;
+
+pub fn build_params(mortality: Mortality, tip: u128) -> TxParams {
+    let params = PolkadotExtrinsicParamsBuilder::<PolkadotConfig>::new().tip(tip);
+    let params = match mortality {
+        Some((period, block_number, block_hash)) => {
+            params.mortal_unchecked(block_number, block_hash, period)
+        }
+        None => params,
+    };
+    params.build()
+}
+
+fn era_mortality(era: &Era) -> error::Result<Mortality> {
+    match era {
+        Era::Immortal => Ok(None),
+        Era::Mortal {
+            period,
+            block_number,
+            block_hash,
+        } => {
+            let block_hash = parse_hash(block_hash)?;
+            Ok(Some((*period, *block_number, block_hash)))
+        }
+    }
+}
+
+/// Mortality starting at the latest block if requested by the tx options
+pub async fn options_mortality(api: &ClientAPI, options: &TxOptions) -> error::Result<Mortality> {
+    let Some(period) = options.mortality else {
+        return Ok(None);
+    };
+    let block = api.blocks().at_latest().await.map_err(map_subxt_err)?;
+    Ok(Some((period, block.number().into(), block.hash())))
+}
+
+pub fn options_tip(op: &impl Operation) -> u128 {
+    op.tx_options()
+        .and_then(|options| options.tip)
+        .map(u128::from)
+        .unwrap_or_default()
+}
+
+async fn prepare_operation<O: Operation>(
+    data: &AppState,
+    input: serde_json::Value,
+) -> error::Result<HttpResponse> {
+    let op: O = parse_operation(input)?;
+    let account = external_signer(op.signer())?;
+    let call = encode_call(&data.api, &op)?;
+    let api = &data.api;
+
+    let options = op.tx_options().cloned().unwrap_or_default();
+    let nonce = match options.nonce {
+        Some(nonce) => nonce,
+        None => api
+            .tx()
+            .account_nonce(&account)
+            .await
+            .map_err(map_subxt_err)?,
+    };
+    let mortality = options_mortality(api, &options).await?;
+    let era = match mortality {
+        Some((period, block_number, block_hash)) => Era::Mortal {
+            period,
+            block_number,
+            block_hash: format!("{:?}", block_hash),
+        },
+        None => Era::Immortal,
+    };
+    let params = build_params(mortality, options_tip(&op));
+    let partial = api
+        .tx()
+        .create_partial_signed_with_nonce(&call, &account, nonce, params)
+        .map_err(map_subxt_err)?;
+    let runtime_version = api.runtime_version();
+
+    Ok(HttpResponse::Ok().json(PrepareExtrinsicOutput {
+        call_data: format!("0x{}", hex::encode(&call.0)),
+        signer_payload: format!("0x{}", hex::encode(partial.signer_payload())),
+        signer_account: account.into(),
+        nonce,
+        era,
+        genesis_hash: format!("{:?}", api.genesis_hash()),
+        spec_version: runtime_version.spec_version,
+        transaction_version: runtime_version.transaction_version,
+    }))
+}
+
+async fn submit_operation<O: Operation>(
+    data: &AppState,
+    req: SubmitExtrinsicInput,
+) -> error::Result<HttpResponse> {
+    let op: O = parse_operation(req.input)?;
+    let account = external_signer(op.signer())?;
+    let call = encode_call(&data.api, &op)?;
+    let api = &data.api;
+
+    let signature_err = |reason: &str| {
+        map_validation_err(vec![FieldError {
+            field: "signature".into(),
+            reason: reason.into(),
+        }])
+    };
+    let signature = hex::decode(req.signature.trim_start_matches("0x"))
+        .map_err(|e| signature_err(&format!("invalid hex: {}", e)))?;
+    let signature = match req.scheme.unwrap_or_default() {
+        CryptoScheme::Sr25519 => <[u8; 64]>::try_from(signature).map(MultiSignature::Sr25519),
+        CryptoScheme::Ed25519 => <[u8; 64]>::try_from(signature).map(MultiSignature::Ed25519),
+        CryptoScheme::Ecdsa => <[u8; 65]>::try_from(signature).map(MultiSignature::Ecdsa),
+    }
+    .map_err(|_| signature_err("expected 64 bytes, or 65 bytes for ecdsa"))?;
+
+    let params = build_params(era_mortality(&req.era)?, options_tip(&op));
+    let partial = api
+        .tx()
+        .create_partial_signed_with_nonce(&call, &account, req.nonce, params)
+        .map_err(map_subxt_err)?;
+    let extrinsic = partial.sign_with_address_and_signature(&account.clone().into(), &signature);
+
+    let progress = extrinsic.submit_and_watch().await.map_err(map_subxt_err)?;
+    // The nonce was chosen outside the API and may be the one held locally
+    data.nonces.resync(&account).await;
+    idempotency::submitted(&format!("{:?}", progress.extrinsic_hash()));
+    wait_for(data, op, progress, account).await
+}
+
+/// Build the payload of an operation for a key held outside the API
+pub async fn prepare(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    req: web::Json<serde_json::Value>,
+) -> error::Result<HttpResponse> {
+    let (module, operation) = path.into_inner();
+    let input = req.into_inner();
+    with_operation!(
+        module.as_str(),
+        operation.as_str(),
+        prepare_operation(&data, input)
+    )
+}
+
+/// Submit an operation prepared with `prepare` along with its signature
+pub async fn submit(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    req: web::Json<SubmitExtrinsicInput>,
+) -> error::Result<HttpResponse> {
+    let (module, operation) = path.into_inner();
+    let req = req.into_inner();
+    with_operation!(
+        module.as_str(),
+        operation.as_str(),
+        submit_operation(&data, req)
+    )
+}
@@ -1,12 +1,21 @@
+use crate::state::*;
 use actix_web::error;
+use codec::Decode;
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::str::FromStr;
+use subxt::blocks::Block;
+use subxt::config::Config;
 use subxt::error::DispatchError;
-use subxt::ext::sp_core::sr25519::Pair as Sr25519Pair;
-use subxt::ext::sp_core::Pair;
-use sugarfunge_api_types::primitives::*;
+use subxt::ext::sp_core::{ecdsa, ed25519, sr25519, Pair};
+use subxt::tx::{PairSigner, Signer};
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use sugarfunge_api_types::primitives::{Signer as SignerInput, *};
+use sugarfunge_api_types::sugarfunge::runtime_types::sp_runtime::DispatchError as RuntimeDispatchError;
 use sugarfunge_api_types::sugarfunge::{self};
+use sugarfunge_api_types::validation::FieldError;
 use url::Url;
 
 #[derive(Serialize, Deserialize, Debug, Display)]
@@ -16,6 +25,31 @@ pub struct RequestError {
     pub description: String,
 }
 
+pub fn map_request_err(req_error: RequestError) -> actix_web::Error {
+    let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+    error::ErrorBadRequest(req_error)
+}
+
+/// Fields of a request that can't be submitted as they are
+pub fn map_validation_err(errors: Vec<FieldError>) -> actix_web::Error {
+    let req_error = RequestError {
+        message: json!(errors),
+        description: "Validation error".into(),
+    };
+    let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+    error::ErrorUnprocessableEntity(req_error)
+}
+
+/// JSON metadata as stored on chain
+pub fn metadata_bytes(metadata: &serde_json::Value) -> error::Result<Vec<u8>> {
+    serde_json::to_vec(metadata).map_err(|e| {
+        map_validation_err(vec![FieldError {
+            field: "metadata".into(),
+            reason: e.to_string(),
+        }])
+    })
+}
+
 pub fn map_subxt_err(e: subxt::Error) -> actix_web::Error {
     // TODO: json_err should be a json Value to improve UX
     let json_err = json!(e.to_string());
@@ -50,6 +84,33 @@ pub fn map_sf_err(e: subxt::Error) -> actix_web::Error {
     error::ErrorBadRequest(req_error)
 }
 
+/// Pallet and variant of a module error, or the debug output of any other
+pub fn dispatch_error_value(e: &DispatchError) -> serde_json::Value {
+    if let DispatchError::Module(module_err) = e {
+        if let Ok(value) = module_err.details() {
+            return json!(&format!(
+                "Pallet: {}, Variant: {}",
+                value.pallet.name(),
+                value.variant.name
+            ));
+        }
+    }
+    json!(&format!("{:?}", e))
+}
+
+/// Pallet and variant of a module error reported inside an event,
+/// e.g. the result of a call dispatched by `sudo`
+pub fn runtime_dispatch_error_value(e: &RuntimeDispatchError) -> serde_json::Value {
+    if let RuntimeDispatchError::Module(module_err) = e {
+        let mut bytes = vec![module_err.index];
+        bytes.extend_from_slice(&module_err.error);
+        if let Ok(value) = sugarfunge::Error::decode(&mut &bytes[..]) {
+            return json!(&format!("{:?}", value));
+        }
+    }
+    json!(&format!("{:?}", e))
+}
+
 pub fn map_account_err(e: sp_core::crypto::PublicError) -> actix_web::Error {
     let json_err: serde_json::Value = json!("Invalid account");
     let req_error = RequestError {
@@ -60,8 +121,123 @@ pub fn map_account_err(e: sp_core::crypto::PublicError) -> actix_web::Error {
     error::ErrorBadRequest(req_error)
 }
 
-pub fn get_pair_from_seed(seed: &Seed) -> error::Result<Sr25519Pair> {
-    Sr25519Pair::from_string(seed.as_str(), None).map_err(|e| {
+/// Hex encoded block or proposal hash
+pub fn parse_hash(hash: &str) -> error::Result<H256> {
+    H256::from_str(hash).map_err(|e| {
+        map_request_err(RequestError {
+            message: json!(e.to_string()),
+            description: "Invalid hash".into(),
+        })
+    })
+}
+
+/// Block at the given hash or number, the latest one if not given
+pub async fn block_at(
+    data: &AppState,
+    at: Option<&BlockAt>,
+) -> error::Result<Block<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
+    let hash = match at {
+        None => return data.api.blocks().at_latest().await.map_err(map_subxt_err),
+        Some(BlockAt::Hash(hash)) => parse_hash(hash)?,
+        Some(BlockAt::Number(number)) => data
+            .rpc
+            .chain_get_block_hash(Some((*number).into()))
+            .await
+            .map_err(map_subxt_err)?
+            .ok_or_else(|| {
+                map_request_err(RequestError {
+                    message: json!(format!("Unknown block {}", number)),
+                    description: "Invalid block".into(),
+                })
+            })?,
+    };
+    data.api.blocks().at(hash).await.map_err(map_subxt_err)
+}
+
+pub fn at_block(block: &Block<PolkadotConfig, OnlineClient<PolkadotConfig>>) -> AtBlock {
+    AtBlock {
+        block_hash: format!("{:?}", block.hash()),
+        block_number: block.number(),
+    }
+}
+
+/// Signer for any of the supported crypto schemes
+pub enum AnySigner {
+    Sr25519(PairSigner<PolkadotConfig, sr25519::Pair>),
+    Ed25519(PairSigner<PolkadotConfig, ed25519::Pair>),
+    Ecdsa(PairSigner<PolkadotConfig, ecdsa::Pair>),
+}
+
+impl AnySigner {
+    pub fn public_key(&self) -> Vec<u8> {
+        match self {
+            AnySigner::Sr25519(signer) => signer.signer().public().as_ref().to_vec(),
+            AnySigner::Ed25519(signer) => signer.signer().public().as_ref().to_vec(),
+            AnySigner::Ecdsa(signer) => signer.signer().public().as_ref().to_vec(),
+        }
+    }
+
+    pub fn scheme(&self) -> CryptoScheme {
+        match self {
+            AnySigner::Sr25519(_) => CryptoScheme::Sr25519,
+            AnySigner::Ed25519(_) => CryptoScheme::Ed25519,
+            AnySigner::Ecdsa(_) => CryptoScheme::Ecdsa,
+        }
+    }
+}
+
+impl Signer<PolkadotConfig> for AnySigner {
+    fn account_id(&self) -> <PolkadotConfig as Config>::AccountId {
+        match self {
+            AnySigner::Sr25519(signer) => Signer::<PolkadotConfig>::account_id(signer),
+            AnySigner::Ed25519(signer) => Signer::<PolkadotConfig>::account_id(signer),
+            AnySigner::Ecdsa(signer) => Signer::<PolkadotConfig>::account_id(signer),
+        }
+    }
+
+    fn address(&self) -> <PolkadotConfig as Config>::Address {
+        match self {
+            AnySigner::Sr25519(signer) => Signer::<PolkadotConfig>::address(signer),
+            AnySigner::Ed25519(signer) => Signer::<PolkadotConfig>::address(signer),
+            AnySigner::Ecdsa(signer) => Signer::<PolkadotConfig>::address(signer),
+        }
+    }
+
+    fn sign(&self, signer_payload: &[u8]) -> <PolkadotConfig as Config>::Signature {
+        match self {
+            AnySigner::Sr25519(signer) => Signer::<PolkadotConfig>::sign(signer, signer_payload),
+            AnySigner::Ed25519(signer) => Signer::<PolkadotConfig>::sign(signer, signer_payload),
+            AnySigner::Ecdsa(signer) => Signer::<PolkadotConfig>::sign(signer, signer_payload),
+        }
+    }
+}
+
+/// Derive the signer of a seed or mnemonic along a `//hard/soft` derivation path
+pub fn get_signer_from_suri(
+    seed: &Seed,
+    derivation_path: Option<&str>,
+    password: Option<&str>,
+    scheme: CryptoScheme,
+) -> error::Result<AnySigner> {
+    let derivation_path = derivation_path.unwrap_or_default();
+    if !derivation_path.is_empty() && !derivation_path.starts_with('/') {
+        let req_error = RequestError {
+            message: json!("Derivation path must start with '/'"),
+            description: "API error".into(),
+        };
+        let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+        return Err(error::ErrorBadRequest(req_error));
+    }
+    let suri = format!("{}{}", seed.as_str(), derivation_path);
+    let signer = match scheme {
+        CryptoScheme::Sr25519 => sr25519::Pair::from_string(&suri, password)
+            .map(|pair| AnySigner::Sr25519(PairSigner::new(pair))),
+        CryptoScheme::Ed25519 => ed25519::Pair::from_string(&suri, password)
+            .map(|pair| AnySigner::Ed25519(PairSigner::new(pair))),
+        CryptoScheme::Ecdsa => ecdsa::Pair::from_string(&suri, password)
+            .map(|pair| AnySigner::Ecdsa(PairSigner::new(pair))),
+    };
+    signer.map_err(|e| {
         let req_error = RequestError {
             message: json!(&format!("{:?}", e)),
             description: "API error".into(),
@@ -71,6 +247,29 @@ pub fn get_pair_from_seed(seed: &Seed) -> error::Result<Sr25519Pair> {
     })
 }
 
+/// Resolve the signer from a seed or a keystore reference
+pub fn get_signer(data: &AppState, signer: &SignerInput) -> error::Result<AnySigner> {
+    let (seed, scheme) = match (&signer.seed, &signer.key_id, &signer.signer_account) {
+        (Some(seed), None, None) => (seed.clone(), signer.scheme.unwrap_or_default()),
+        (None, Some(key_id), None) => data.keystore.seed_by_key_id(key_id)?,
+        (None, None, Some(account)) => data.keystore.seed_by_account(account)?,
+        _ => {
+            let req_error = RequestError {
+                message: json!("Exactly one of seed, key_id or signer_account must be provided"),
+                description: "API error".into(),
+            };
+            let req_error = serde_json::to_string_pretty(&req_error).unwrap();
+            return Err(error::ErrorBadRequest(req_error));
+        }
+    };
+    get_signer_from_suri(
+        &seed,
+        signer.derivation_path.as_deref(),
+        signer.password.as_deref(),
+        scheme,
+    )
+}
+
 pub fn url_to_string(url: Url) -> String {
     let mut res = url.to_string();
     match (url.port(), url.port_or_known_default()) {
@@ -0,0 +1,29 @@
+use crate::state::*;
+use crate::util::*;
+use actix_web::error;
+use sugarfunge_api_types::validation::*;
+
+fn constant(api: &ClientAPI, pallet: &str, name: &str) -> Option<u32> {
+    let address = subxt::dynamic::constant(pallet, name);
+    let value = api.constants().at(&address).ok()?.to_value().ok()?;
+    value.as_u128().and_then(|value| u32::try_from(value).ok())
+}
+
+/// Limits of the runtime the client is connected to
+pub fn limits(api: &ClientAPI) -> Limits {
+    Limits {
+        max_class_metadata: constant(api, "Asset", "MaxClassMetadata"),
+        max_asset_metadata: constant(api, "Asset", "MaxAssetMetadata"),
+        max_bundle_assets: constant(api, "Bundle", "MaxAssets"),
+        max_bag_owners: constant(api, "Bag", "MaxOwners"),
+        max_market_rates: constant(api, "Market", "MaxRates"),
+    }
+}
+
+/// Check an input against the runtime limits, fails with all the
+/// offending fields
+pub fn validate<V: Validate>(api: &ClientAPI, input: &V) -> error::Result<()> {
+    let mut validator = Validator::new(limits(api));
+    input.validate(&mut validator);
+    validator.finish().map_err(map_validation_err)
+}